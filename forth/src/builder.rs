@@ -0,0 +1,105 @@
+use crate::{Cell, Forth, Value};
+use std::marker::PhantomData;
+
+/// Configures a [`Forth`] interpreter before it is created.
+///
+/// Every limit defaults to unbounded. Exceeding a limit aborts `eval` with
+/// the matching `Error` variant, which makes it safe to run untrusted input:
+///
+/// ```
+/// use forth::{Error, ForthBuilder};
+///
+/// let mut f = ForthBuilder::new().max_steps(1_000).build();
+/// assert_eq!(Err(Error::StepLimitExceeded), f.eval("begin 0 again"));
+/// ```
+///
+/// Interpreters with a cell type other than [`Value`] start from
+/// `ForthBuilder::<C>::default()`.
+#[derive(Clone, Debug)]
+pub struct ForthBuilder<C: Cell = Value> {
+    limits: Limits,
+    checked: bool,
+    cell: PhantomData<C>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Limits {
+    pub steps: usize,
+    pub stack_depth: usize,
+    pub return_depth: usize,
+    pub dictionary_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            steps: usize::MAX,
+            stack_depth: usize::MAX,
+            return_depth: usize::MAX,
+            dictionary_bytes: usize::MAX,
+        }
+    }
+}
+
+impl<C: Cell> Default for ForthBuilder<C> {
+    fn default() -> Self {
+        ForthBuilder {
+            limits: Limits::default(),
+            checked: false,
+            cell: PhantomData,
+        }
+    }
+}
+
+impl ForthBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<C: Cell> ForthBuilder<C> {
+    /// Whether arithmetic fails with `Error::Overflow` instead of wrapping.
+    ///
+    /// For floating-point cells, any infinite or NaN result is an overflow.
+    pub fn checked_arithmetic(mut self, checked: bool) -> Self {
+        self.checked = checked;
+        self
+    }
+
+    /// The number of instructions a single `eval` may execute.
+    ///
+    /// Fails with `Error::StepLimitExceeded`.
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.limits.steps = steps;
+        self
+    }
+
+    /// The number of cells the data stack may hold.
+    ///
+    /// Fails with `Error::DataStackOverflow`.
+    pub fn max_stack_depth(mut self, depth: usize) -> Self {
+        self.limits.stack_depth = depth;
+        self
+    }
+
+    /// The number of nested calls to user-defined words.
+    ///
+    /// A control structure run outside a definition is not a call, and does
+    /// not count. Fails with `Error::ReturnStackOverflow`.
+    pub fn max_return_depth(mut self, depth: usize) -> Self {
+        self.limits.return_depth = depth;
+        self
+    }
+
+    /// The number of bytes user definitions and the data space may take up.
+    ///
+    /// Built-in words are not counted. Fails with `Error::DictionaryFull`.
+    pub fn max_dictionary_bytes(mut self, bytes: usize) -> Self {
+        self.limits.dictionary_bytes = bytes;
+        self
+    }
+
+    pub fn build(self) -> Forth<C> {
+        Forth::configured(self.limits, self.checked)
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+/// A value that fits in a cell of the data stack or the data space.
+///
+/// Implemented for `i32`, `i64` and `f64`. Integer cells wrap on overflow
+/// unless the interpreter is built with checked arithmetic; float cells
+/// follow IEEE 754, and in checked mode any non-finite result counts as an
+/// overflow.
+pub trait Cell: Copy + PartialOrd + fmt::Debug + fmt::Display + 'static {
+    const ZERO: Self;
+    const ONE: Self;
+    /// The flag pushed for a true comparison.
+    const TRUE: Self;
+    /// Identifies the cell type in a saved session.
+    const NAME: &'static str;
+
+    /// Parse a number literal, or return `None` if `token` is a word.
+    fn parse(token: &str) -> Option<Self>;
+
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    fn wrapping_div(self, rhs: Self) -> Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+
+    /// Convert to a count or address, if the value is a whole number in range.
+    fn to_isize(self) -> Option<isize>;
+
+    /// Convert a count or address to a cell, if it is in range.
+    fn from_usize(n: usize) -> Option<Self>;
+
+    /// The bit pattern a cell is saved as.
+    fn to_bits(self) -> u64;
+
+    /// Restore a cell saved by [`Cell::to_bits`], if `bits` is one.
+    fn from_bits(bits: u64) -> Option<Self>;
+}
+
+macro_rules! integer_cells {
+    ($($int:ty),* $(,)?) => {
+        $(
+            impl Cell for $int {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TRUE: Self = -1;
+                const NAME: &'static str = stringify!($int);
+
+                fn parse(token: &str) -> Option<Self> {
+                    token.parse().ok()
+                }
+
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$int>::wrapping_add(self, rhs)
+                }
+
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$int>::wrapping_sub(self, rhs)
+                }
+
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$int>::wrapping_mul(self, rhs)
+                }
+
+                fn wrapping_div(self, rhs: Self) -> Self {
+                    <$int>::wrapping_div(self, rhs)
+                }
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$int>::checked_div(self, rhs)
+                }
+
+                fn to_isize(self) -> Option<isize> {
+                    isize::try_from(self).ok()
+                }
+
+                fn from_usize(n: usize) -> Option<Self> {
+                    Self::try_from(n).ok()
+                }
+
+                fn to_bits(self) -> u64 {
+                    i64::from(self) as u64
+                }
+
+                fn from_bits(bits: u64) -> Option<Self> {
+                    Self::try_from(bits as i64).ok()
+                }
+            }
+        )*
+    };
+}
+
+integer_cells!(i32, i64);
+
+impl Cell for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const TRUE: Self = -1.0;
+    const NAME: &'static str = "f64";
+
+    /// Only tokens that start like a number are parsed, so that words such
+    /// as `inf` and `nan` stay available as names.
+    fn parse(token: &str) -> Option<Self> {
+        let digits = token.trim_start_matches(['+', '-']).trim_start_matches('.');
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        token.parse().ok()
+    }
+
+    fn wrapping_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+
+    fn wrapping_mul(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    fn wrapping_div(self, rhs: Self) -> Self {
+        self / rhs
+    }
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(self + rhs).filter(|n| n.is_finite())
+    }
+
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Some(self - rhs).filter(|n| n.is_finite())
+    }
+
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        Some(self * rhs).filter(|n| n.is_finite())
+    }
+
+    fn checked_div(self, rhs: Self) -> Option<Self> {
+        Some(self / rhs).filter(|n| n.is_finite())
+    }
+
+    fn to_isize(self) -> Option<isize> {
+        let in_range = self >= isize::MIN as f64 && self < isize::MAX as f64;
+        (self.fract() == 0.0 && in_range).then_some(self as isize)
+    }
+
+    fn from_usize(n: usize) -> Option<Self> {
+        Some(n as f64)
+    }
+
+    fn to_bits(self) -> u64 {
+        f64::to_bits(self)
+    }
+
+    fn from_bits(bits: u64) -> Option<Self> {
+        Some(f64::from_bits(bits))
+    }
+}
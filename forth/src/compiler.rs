@@ -0,0 +1,146 @@
+use crate::{Cell, Error, Op};
+
+/// Accumulates the code of a definition and resolves its structured
+/// control flow into branches.
+///
+/// Forward branches are emitted with a placeholder target and patched once
+/// the matching `THEN` or `REPEAT` is seen; backward branches know their
+/// target from the `BEGIN` or `DO` that opened the structure.
+pub struct Compiler<C: Cell> {
+    code: Vec<Op<C>>,
+    control: Vec<Control>,
+}
+
+/// An open control structure, remembering the ip it needs to branch to or
+/// patch when it is closed.
+enum Control {
+    If(usize),
+    Else(usize),
+    Begin(usize),
+    While { begin: usize, exit: usize },
+    Do(usize),
+}
+
+impl<C: Cell> Default for Compiler<C> {
+    fn default() -> Self {
+        Compiler {
+            code: Vec::new(),
+            control: Vec::new(),
+        }
+    }
+}
+
+impl<C: Cell> Compiler<C> {
+    pub fn push(&mut self, op: Op<C>) {
+        self.code.push(op);
+    }
+
+    /// Whether every control structure opened so far has been closed.
+    pub fn is_balanced(&self) -> bool {
+        self.control.is_empty()
+    }
+
+    /// Compile `word` if it is a control-flow word.
+    ///
+    /// Returns `Ok(false)` if `word` is not a control-flow word, and
+    /// `Err(Error::UnbalancedControlFlow)` if it closes a structure that was
+    /// never opened.
+    pub fn control(&mut self, word: &str) -> Result<bool, Error> {
+        let here = self.code.len();
+        match word {
+            "if" => {
+                self.code.push(Op::ZeroBranch(0));
+                self.control.push(Control::If(here));
+            }
+            "else" => {
+                let Some(Control::If(orig)) = self.control.pop() else {
+                    return Err(Error::UnbalancedControlFlow);
+                };
+                self.code.push(Op::Branch(0));
+                self.patch(orig, here + 1);
+                self.control.push(Control::Else(here));
+            }
+            "then" => match self.control.pop() {
+                Some(Control::If(orig) | Control::Else(orig)) => self.patch(orig, here),
+                _ => return Err(Error::UnbalancedControlFlow),
+            },
+            "begin" => self.control.push(Control::Begin(here)),
+            "until" | "again" => {
+                let Some(Control::Begin(begin)) = self.control.pop() else {
+                    return Err(Error::UnbalancedControlFlow);
+                };
+                self.code.push(match word {
+                    "until" => Op::ZeroBranch(begin),
+                    _ => Op::Branch(begin),
+                });
+            }
+            "while" => {
+                let Some(Control::Begin(begin)) = self.control.pop() else {
+                    return Err(Error::UnbalancedControlFlow);
+                };
+                self.code.push(Op::ZeroBranch(0));
+                self.control.push(Control::While { begin, exit: here });
+            }
+            "repeat" => {
+                let Some(Control::While { begin, exit }) = self.control.pop() else {
+                    return Err(Error::UnbalancedControlFlow);
+                };
+                self.code.push(Op::Branch(begin));
+                self.patch(exit, here + 1);
+            }
+            "do" => {
+                self.code.push(Op::Do);
+                self.control.push(Control::Do(here + 1));
+            }
+            "loop" => {
+                let Some(Control::Do(body)) = self.control.pop() else {
+                    return Err(Error::UnbalancedControlFlow);
+                };
+                self.code.push(Op::Loop(body));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Terminate the code with `Op::Exit` and return it.
+    pub fn finish(mut self) -> Result<Vec<Op<C>>, Error> {
+        if !self.is_balanced() {
+            return Err(Error::UnbalancedControlFlow);
+        }
+        self.code.push(Op::Exit);
+        Ok(self.code)
+    }
+
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.code[at] {
+            Op::Branch(t) | Op::ZeroBranch(t) => *t = target,
+            op => unreachable!("cannot patch {op}"),
+        }
+    }
+}
+
+/// Whether `word` is handled by the outer interpreter or the compiler rather
+/// than looked up in the dictionary.
+pub fn is_keyword(word: &str) -> bool {
+    matches!(
+        word,
+        ":" | ";"
+            | "variable"
+            | "constant"
+            | "create"
+            | "see"
+            | "s\""
+            | ".\""
+            | "if"
+            | "else"
+            | "then"
+            | "begin"
+            | "until"
+            | "again"
+            | "while"
+            | "repeat"
+            | "do"
+            | "loop"
+    )
+}
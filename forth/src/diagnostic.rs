@@ -0,0 +1,46 @@
+use crate::Error;
+use std::fmt;
+
+/// An [`Error`] together with where in the input it happened.
+///
+/// Returned by [`Forth::eval_with_diagnostics`](crate::Forth::eval_with_diagnostics).
+/// Displays as `line:column: error `token`` followed by one `in word` line
+/// per user-defined word that was executing, innermost first.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub error: Error,
+    /// The token the outer interpreter was running or compiling.
+    pub token: String,
+    /// The 1-based line of `token`.
+    pub line: usize,
+    /// The 1-based column of `token`, in characters.
+    pub column: usize,
+    /// The user-defined words being executed, innermost first.
+    pub backtrace: Vec<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} `{}`",
+            self.line, self.column, self.error, self.token
+        )?;
+        for word in &self.backtrace {
+            write!(f, "\n    in {word}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<Diagnostic> for Error {
+    fn from(diagnostic: Diagnostic) -> Self {
+        diagnostic.error
+    }
+}
@@ -1,7 +1,99 @@
+mod builder;
+mod cell;
+mod compiler;
+mod diagnostic;
+mod op;
+mod scanner;
+mod snapshot;
+mod stack;
+
+pub use builder::ForthBuilder;
+pub use cell::Cell;
+pub use diagnostic::Diagnostic;
+pub use op::Op;
+pub use snapshot::Snapshot;
+pub use stack::Stack;
+
+use builder::Limits;
+use compiler::Compiler;
+use scanner::Scanner;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::io::{self, Write as _};
+use std::rc::Rc;
+
 pub type Value = i32;
 pub type Result = std::result::Result<(), Error>;
 
-pub struct Forth;
+/// A Forth interpreter.
+///
+/// Words defined with `: name ... ;` are compiled once into a flat list of
+/// [`Op`]s and run by an inner interpreter with its own return stack. A
+/// compiled word refers to the words it uses by dictionary index, which gives
+/// the usual Forth snapshot semantics for redefinitions and keeps deeply
+/// nested definitions the same size as their source.
+///
+/// The structured control-flow words (`IF`/`ELSE`/`THEN`, `DO`/`LOOP` and
+/// `BEGIN`/`UNTIL`/`AGAIN`/`WHILE`/`REPEAT`) are compiled to branches. Outside
+/// a definition, a control structure is compiled as an anonymous word and run
+/// as soon as it is closed.
+///
+/// `VARIABLE`, `CONSTANT` and `CREATE` define words over a linear data space
+/// of cells, addressed by cell index from zero, which `@`, `!`, `+!`, `HERE`,
+/// `ALLOT` and `,` operate on.
+///
+/// The host can extend the dictionary with native words written in Rust using
+/// [`Forth::register_native`].
+///
+/// `.`, `EMIT`, `CR`, `TYPE`, `."`, `.S`, `WORDS` and `SEE` write to the
+/// interpreter's output, which is standard output unless replaced with
+/// [`Forth::set_output`]. `KEY` and `ACCEPT` read from its input, standard
+/// input unless replaced with [`Forth::set_input`]. String literals made by
+/// `S"` and `."` in a definition are stored in the data space, one character
+/// per cell. Outside a definition, `."` prints its text straight away and
+/// `S"` leaves its text in a transient buffer just past the end of the data
+/// space, which the next `S"` reuses and which is overwritten once the data
+/// space grows.
+///
+/// A session can be checkpointed with [`Forth::snapshot`] and rolled back
+/// with [`Forth::restore`], or saved to disk with [`Forth::save`] and resumed
+/// in another process with [`Forth::load`].
+///
+/// [`Forth::eval_with_diagnostics`] reports where in the input an error
+/// happened and which words were executing at the time.
+///
+/// Use a [`ForthBuilder`] to bound the resources an interpreter may consume.
+///
+/// Cells hold a [`Value`] unless another [`Cell`] type is chosen, such as
+/// `i64` for wide counters or `f64` for floating-point math:
+///
+/// ```
+/// let mut f = forth::Forth::<f64>::default();
+/// f.eval("1 4 /").unwrap();
+/// assert_eq!(&[0.25], f.stack());
+/// ```
+pub struct Forth<C: Cell = Value> {
+    stack: Stack<C>,
+    memory: Vec<C>,
+    /// The text of the last `S"` run outside a definition, addressed just
+    /// past the end of the data space.
+    transient: Vec<C>,
+    words: Vec<Word<C>>,
+    names: HashMap<Rc<str>, usize>,
+    returns: Vec<Frame<C>>,
+    loops: Vec<LoopFrame<C>>,
+    /// The words that were executing when the last error was raised.
+    backtrace: Vec<String>,
+    output: Box<dyn io::Write>,
+    input: Box<dyn io::BufRead>,
+    limits: Limits,
+    /// Whether arithmetic fails with `Error::Overflow` rather than wrapping.
+    checked: bool,
+    /// Instructions executed by the current `eval`.
+    steps: usize,
+    /// Bytes taken up by user definitions and the data space.
+    dictionary_bytes: usize,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Error {
@@ -9,18 +101,917 @@ pub enum Error {
     StackUnderflow,
     UnknownWord,
     InvalidWord,
+    UnbalancedControlFlow,
+    InvalidAddress,
+    /// A failure reported by a native word.
+    Native(String),
+    StepLimitExceeded,
+    DataStackOverflow,
+    ReturnStackOverflow,
+    DictionaryFull,
+    Overflow,
+    /// Reading the input or writing the output failed.
+    Io(io::ErrorKind),
+    /// A saved session could not be decoded.
+    InvalidSnapshot,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DivisionByZero => write!(f, "division by zero"),
+            Error::StackUnderflow => write!(f, "stack underflow"),
+            Error::UnknownWord => write!(f, "unknown word"),
+            Error::InvalidWord => write!(f, "invalid word"),
+            Error::UnbalancedControlFlow => write!(f, "unbalanced control flow"),
+            Error::InvalidAddress => write!(f, "invalid address"),
+            Error::Native(message) => write!(f, "native word failed: {message}"),
+            Error::StepLimitExceeded => write!(f, "step limit exceeded"),
+            Error::DataStackOverflow => write!(f, "data stack overflow"),
+            Error::ReturnStackOverflow => write!(f, "return stack overflow"),
+            Error::DictionaryFull => write!(f, "dictionary full"),
+            Error::Overflow => write!(f, "arithmetic overflow"),
+            Error::Io(kind) => write!(f, "i/o failed: {kind}"),
+            Error::InvalidSnapshot => write!(f, "invalid snapshot"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e.kind())
+    }
+}
+
+/// A dictionary entry.
+#[derive(Clone)]
+struct Word<C: Cell> {
+    name: Rc<str>,
+    body: Body<C>,
+}
+
+#[derive(Clone)]
+enum Body<C: Cell> {
+    /// A word that is a single op, inlined into any definition that uses it:
+    /// a built-in, a constant or the address of a variable.
+    Inline(Op<C>),
+    /// A user-defined word, always terminated by `Op::Exit`.
+    Colon(Rc<[Op<C>]>),
+    /// A word implemented by the host.
+    Native(NativeFn<C>),
+}
+
+type NativeFn<C> = Rc<dyn Fn(&mut Stack<C>) -> Result>;
+
+/// A return address: the code being executed and the next op to run in it.
+struct Frame<C: Cell> {
+    /// The word being executed, or `None` for an anonymous control structure.
+    xt: Option<usize>,
+    code: Rc<[Op<C>]>,
+    ip: usize,
+}
+
+/// The state of a `DO ... LOOP`.
+struct LoopFrame<C: Cell> {
+    index: C,
+    limit: C,
+}
+
+impl<C: Cell> Default for Forth<C> {
+    fn default() -> Self {
+        ForthBuilder::default().build()
+    }
 }
 
 impl Forth {
     pub fn new() -> Forth {
-        todo!()
+        ForthBuilder::new().build()
+    }
+
+    pub fn builder() -> ForthBuilder {
+        ForthBuilder::new()
+    }
+}
+
+impl<C: Cell> Forth<C> {
+    const PRIMITIVES: [(&'static str, Op<C>); 28] = [
+        ("+", Op::Add),
+        ("-", Op::Sub),
+        ("*", Op::Mul),
+        ("/", Op::Div),
+        ("dup", Op::Dup),
+        ("drop", Op::Drop),
+        ("swap", Op::Swap),
+        ("over", Op::Over),
+        ("=", Op::Eq),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+        ("0=", Op::ZeroEq),
+        ("i", Op::I),
+        ("j", Op::J),
+        ("@", Op::Fetch),
+        ("!", Op::Store),
+        ("+!", Op::PlusStore),
+        ("here", Op::Here),
+        ("allot", Op::Allot),
+        (",", Op::Comma),
+        (".", Op::Dot),
+        ("emit", Op::Emit),
+        ("type", Op::Type),
+        ("key", Op::Key),
+        ("accept", Op::Accept),
+        ("cr", Op::Cr),
+        (".s", Op::DotS),
+        ("words", Op::Words),
+    ];
+
+    pub(crate) fn configured(limits: Limits, checked: bool) -> Self {
+        let mut forth = Forth {
+            stack: Stack::default(),
+            memory: Vec::new(),
+            transient: Vec::new(),
+            words: Vec::new(),
+            names: HashMap::new(),
+            returns: Vec::new(),
+            loops: Vec::new(),
+            backtrace: Vec::new(),
+            output: Box::new(io::stdout()),
+            input: Box::new(io::BufReader::new(io::stdin())),
+            limits,
+            checked,
+            steps: 0,
+            dictionary_bytes: 0,
+        };
+        for (name, op) in Self::PRIMITIVES {
+            forth.insert(name.to_string(), Body::Inline(op));
+        }
+        forth
+    }
+
+    pub fn stack(&self) -> &[C] {
+        self.stack.as_slice()
+    }
+
+    /// The data space: every cell allotted so far, by address.
+    pub fn memory(&self) -> &[C] {
+        &self.memory
+    }
+
+    /// Send the output of `.`, `EMIT` and friends to `output`.
+    pub fn set_output<W: io::Write + 'static>(&mut self, output: W) {
+        self.output = Box::new(output);
     }
 
-    pub fn stack(&self) -> &[Value] {
-        todo!()
+    /// Read the input of `KEY` and `ACCEPT` from `input`.
+    pub fn set_input<R: io::BufRead + 'static>(&mut self, input: R) {
+        self.input = Box::new(input);
     }
 
     pub fn eval(&mut self, input: &str) -> Result {
-        todo!("result of evaluating '{input}'")
+        self.eval_with_diagnostics(input).map_err(Error::from)
+    }
+
+    /// Like [`Forth::eval`], but on failure also report the offending token,
+    /// its position in `input` and the words that were executing.
+    pub fn eval_with_diagnostics(&mut self, input: &str) -> std::result::Result<(), Diagnostic> {
+        self.steps = 0;
+        self.backtrace.clear();
+        let mut scanner = Scanner::new(input);
+        let result = self.interpret(&mut scanner);
+        let flushed = self.output.flush().map_err(Error::from);
+
+        result.and(flushed).map_err(|error| {
+            let (line, column) = scanner.last_location();
+            Diagnostic {
+                error,
+                token: scanner.last_token().to_string(),
+                line,
+                column,
+                backtrace: std::mem::take(&mut self.backtrace),
+            }
+        })
+    }
+
+    /// The outer interpreter: run or compile each token of the input.
+    fn interpret(&mut self, scanner: &mut Scanner) -> Result {
+        while let Some(token) = scanner.next_token() {
+            let token = token.to_lowercase();
+            match token.as_str() {
+                ":" => self.define(scanner)?,
+                "variable" | "constant" | "create" => self.define_data(scanner, &token)?,
+                ".\"" => {
+                    let text = scanner.parse_until('"').ok_or(Error::InvalidWord)?;
+                    write!(self.output, "{text}")?;
+                }
+                "s\"" => {
+                    let text = scanner.parse_until('"').ok_or(Error::InvalidWord)?;
+                    self.transient.clear();
+                    self.transient.extend(text.chars().map(char_cell::<C>));
+                    let address = Self::cell_address(self.memory.len())?;
+                    let len = Self::cell_address(self.transient.len())?;
+                    self.step(Op::Lit(address))?;
+                    self.step(Op::Lit(len))?;
+                }
+                "see" => {
+                    let name = scanner.next_token().ok_or(Error::InvalidWord)?;
+                    let listing = self.disassemble(name)?;
+                    write!(self.output, "{listing}")?;
+                }
+                _ if compiler::is_keyword(&token) => self.interpret_control(scanner, token)?,
+                _ => match C::parse(&token) {
+                    Some(n) => self.step(Op::Lit(n))?,
+                    None => self.execute(self.lookup(&token)?)?,
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Define `name` as a word implemented by `f`.
+    ///
+    /// A native word is an ordinary dictionary entry: it shadows any earlier
+    /// word of the same name, can itself be redefined, and definitions that
+    /// used it keep calling it after a redefinition. Errors returned by `f`
+    /// abort `eval` unchanged; natives should report their own failures as
+    /// `Error::Native`.
+    pub fn register_native<F>(&mut self, name: &str, f: F) -> Result
+    where
+        F: Fn(&mut Stack<C>) -> Result + 'static,
+    {
+        let name = name.to_lowercase();
+        Self::check_name(&name)?;
+        self.define_word(name, Body::Native(Rc::new(f)))
+    }
+
+    /// Capture the dictionary, data space and data stack.
+    pub fn snapshot(&self) -> Snapshot<C> {
+        Snapshot {
+            stack: self.stack.clone(),
+            memory: self.memory.clone(),
+            words: self.words.clone(),
+            names: self.names.clone(),
+            dictionary_bytes: self.dictionary_bytes,
+        }
+    }
+
+    /// Return to the state captured by `snapshot`, forgetting every word
+    /// defined since. The output, input and limits are left as they are.
+    pub fn restore(&mut self, snapshot: &Snapshot<C>) {
+        let snapshot = snapshot.clone();
+        self.stack = snapshot.stack;
+        self.memory = snapshot.memory;
+        self.transient.clear();
+        self.words = snapshot.words;
+        self.names = snapshot.names;
+        self.dictionary_bytes = snapshot.dictionary_bytes;
+    }
+
+    /// Write the dictionary, data space and data stack to `writer` in a
+    /// binary format that [`Forth::load`] reads back.
+    ///
+    /// Native words are saved by name, and must be registered again before
+    /// the session is loaded.
+    pub fn save<W: io::Write>(&self, mut writer: W) -> Result {
+        let bytes = self.snapshot().encode(Self::PRIMITIVES.len());
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Replace the session with one written by [`Forth::save`].
+    ///
+    /// Fails with `Error::InvalidSnapshot` if the data is malformed or was
+    /// saved with another cell type, `Error::UnknownWord` if it uses a native
+    /// word this interpreter does not have, and `Error::DictionaryFull` or
+    /// `Error::DataStackOverflow` if it does not fit within the limits. The
+    /// session is left untouched on failure.
+    pub fn load<R: io::Read>(&mut self, mut reader: R) -> Result {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let builtins = &self.words[..Self::PRIMITIVES.len()];
+        let snapshot = Snapshot::decode(&bytes, builtins, |name| {
+            let body = &self.words[self.lookup(name).ok()?].body;
+            matches!(body, Body::Native(_)).then(|| body.clone())
+        })?;
+
+        if snapshot.dictionary_bytes > self.limits.dictionary_bytes {
+            return Err(Error::DictionaryFull);
+        }
+        if snapshot.stack.len() > self.limits.stack_depth {
+            return Err(Error::DataStackOverflow);
+        }
+        self.restore(&snapshot);
+        Ok(())
+    }
+
+    /// Return a listing of the ops `word` was compiled to, one per line.
+    ///
+    /// Calls are shown with the name and dictionary index of their target, so
+    /// a call into a since-redefined word can be told apart from the current
+    /// definition.
+    pub fn disassemble(&self, word: &str) -> std::result::Result<String, Error> {
+        let xt = self.lookup(&word.to_lowercase())?;
+        let native = Op::Native(xt);
+        let code = match &self.words[xt].body {
+            Body::Inline(op) => std::slice::from_ref(op),
+            Body::Colon(code) => code,
+            Body::Native(_) => std::slice::from_ref(&native),
+        };
+
+        let mut listing = String::new();
+        for (ip, op) in code.iter().enumerate() {
+            match op {
+                Op::Call(callee) => {
+                    writeln!(
+                        listing,
+                        "{ip:>4}  CALL {}#{callee}",
+                        self.words[*callee].name
+                    )
+                }
+                Op::Native(callee) => {
+                    writeln!(
+                        listing,
+                        "{ip:>4}  NATIVE {}#{callee}",
+                        self.words[*callee].name
+                    )
+                }
+                _ => writeln!(listing, "{ip:>4}  {op}"),
+            }
+            .expect("writing to a String cannot fail");
+        }
+        Ok(listing)
+    }
+
+    fn lookup(&self, name: &str) -> std::result::Result<usize, Error> {
+        self.names.get(name).copied().ok_or(Error::UnknownWord)
+    }
+
+    fn insert(&mut self, name: String, body: Body<C>) {
+        let name: Rc<str> = name.into();
+        self.names.insert(Rc::clone(&name), self.words.len());
+        self.words.push(Word { name, body });
+    }
+
+    /// Add a user definition to the dictionary, within the dictionary limit.
+    fn define_word(&mut self, name: String, body: Body<C>) -> Result {
+        self.charge(word_bytes(&name, &body))?;
+        self.insert(name, body);
+        Ok(())
+    }
+
+    /// Account for `bytes` more of the dictionary.
+    fn charge(&mut self, bytes: usize) -> Result {
+        let total = self
+            .dictionary_bytes
+            .checked_add(bytes)
+            .filter(|&total| total <= self.limits.dictionary_bytes)
+            .ok_or(Error::DictionaryFull)?;
+        self.dictionary_bytes = total;
+        Ok(())
+    }
+
+    /// Grow or, for negative `cells`, shrink the data space.
+    ///
+    /// Fails with `Error::DictionaryFull` if the dictionary limit is reached
+    /// or the memory cannot be allocated.
+    fn allot(&mut self, cells: C) -> Result {
+        let len = cells
+            .to_isize()
+            .and_then(|cells| self.memory.len().checked_add_signed(cells))
+            .ok_or(Error::InvalidAddress)?;
+        let cell = std::mem::size_of::<C>();
+        if len > self.memory.len() {
+            let bytes = (len - self.memory.len())
+                .checked_mul(cell)
+                .ok_or(Error::DictionaryFull)?;
+            self.charge(bytes)?;
+            // Without a dictionary limit, the allocator is the only limit.
+            if self.memory.try_reserve(len - self.memory.len()).is_err() {
+                self.dictionary_bytes -= bytes;
+                return Err(Error::DictionaryFull);
+            }
+        } else {
+            self.dictionary_bytes -= (self.memory.len() - len) * cell;
+        }
+        self.memory.resize(len, C::ZERO);
+        Ok(())
+    }
+
+    /// Compile `: name ... ;`, with the leading `:` already consumed.
+    ///
+    /// The new name only becomes visible once the definition is complete, so
+    /// a word may be defined in terms of its own previous definition.
+    fn define(&mut self, scanner: &mut Scanner) -> Result {
+        let name = Self::parse_name(scanner)?;
+
+        let mut compiler = Compiler::default();
+        loop {
+            let token = scanner
+                .next_token()
+                .ok_or(Error::InvalidWord)?
+                .to_lowercase();
+            if token == ";" {
+                break;
+            }
+            self.compile(&mut compiler, scanner, &token)?;
+        }
+
+        let code = compiler.finish()?;
+        self.define_word(name, Body::Colon(code.into()))
+    }
+
+    /// Define a word that pushes a single value, with the defining word
+    /// already consumed.
+    ///
+    /// `VARIABLE` allots a zero-initialised cell and pushes its address,
+    /// `CREATE` pushes the address of the next cell to be allotted, and
+    /// `CONSTANT` pushes the value it pops.
+    fn define_data(&mut self, scanner: &mut Scanner, defining: &str) -> Result {
+        let name = Self::parse_name(scanner)?;
+        let value = match defining {
+            "constant" => self.pop()?,
+            "variable" => {
+                self.allot(C::ONE)?;
+                Self::cell_address(self.memory.len() - 1)?
+            }
+            _ => Self::cell_address(self.memory.len())?,
+        };
+        self.define_word(name, Body::Inline(Op::Lit(value)))
+    }
+
+    /// Read the name of a word being defined.
+    fn parse_name(scanner: &mut Scanner) -> std::result::Result<String, Error> {
+        let name = scanner
+            .next_token()
+            .ok_or(Error::InvalidWord)?
+            .to_lowercase();
+        Self::check_name(&name)?;
+        Ok(name)
+    }
+
+    /// Reject names that could never be looked up.
+    fn check_name(name: &str) -> Result {
+        if name.is_empty()
+            || name.contains(char::is_whitespace)
+            || C::parse(name).is_some()
+            || compiler::is_keyword(name)
+        {
+            return Err(Error::InvalidWord);
+        }
+        Ok(())
+    }
+
+    /// Copy the string literal following `S"` or `."` in a definition into
+    /// the data space, returning its address and length in characters.
+    fn store_string(&mut self, scanner: &mut Scanner) -> std::result::Result<(C, C), Error> {
+        let text = scanner.parse_until('"').ok_or(Error::InvalidWord)?;
+        let address = self.memory.len();
+        let len = text.chars().count();
+        self.allot(Self::cell_address(len)?)?;
+        for (cell, c) in self.memory[address..].iter_mut().zip(text.chars()) {
+            *cell = char_cell(c);
+        }
+        Ok((Self::cell_address(address)?, Self::cell_address(len)?))
+    }
+
+    /// Compile the control structure opened by `opener` and everything up to
+    /// its matching close, then run it.
+    ///
+    /// A word that closes a structure is reported as unbalanced here, since
+    /// nothing outside a definition can have opened it.
+    fn interpret_control(&mut self, scanner: &mut Scanner, opener: String) -> Result {
+        let mut compiler = Compiler::default();
+        let mut token = opener;
+        loop {
+            self.compile(&mut compiler, scanner, &token)?;
+            if compiler.is_balanced() {
+                break;
+            }
+            token = scanner
+                .next_token()
+                .ok_or(Error::UnbalancedControlFlow)?
+                .to_lowercase();
+        }
+        self.call(None, compiler.finish()?.into())
+    }
+
+    fn compile(
+        &mut self,
+        compiler: &mut Compiler<C>,
+        scanner: &mut Scanner,
+        token: &str,
+    ) -> Result {
+        if compiler.control(token)? {
+            return Ok(());
+        }
+        if let "s\"" | ".\"" = token {
+            let (address, len) = self.store_string(scanner)?;
+            compiler.push(Op::Lit(address));
+            compiler.push(Op::Lit(len));
+            if token == ".\"" {
+                compiler.push(Op::Type);
+            }
+            return Ok(());
+        }
+        if compiler::is_keyword(token) {
+            return Err(Error::InvalidWord);
+        }
+        if let Some(n) = C::parse(token) {
+            compiler.push(Op::Lit(n));
+            return Ok(());
+        }
+
+        let xt = self.lookup(token)?;
+        compiler.push(match self.words[xt].body {
+            Body::Inline(op) => op,
+            Body::Colon(_) => Op::Call(xt),
+            Body::Native(_) => Op::Native(xt),
+        });
+        Ok(())
+    }
+
+    /// Run the word `xt` to completion.
+    fn execute(&mut self, xt: usize) -> Result {
+        match &self.words[xt].body {
+            Body::Inline(op) => self.step(*op),
+            Body::Colon(code) => self.call(Some(xt), Rc::clone(code)),
+            Body::Native(_) => self.step(Op::Native(xt)),
+        }
+    }
+
+    /// Run `code`, the body of `xt` if it is a named word, to completion.
+    ///
+    /// Leaves the return and loop stacks empty, recording the words on the
+    /// return stack as the backtrace if the code fails.
+    fn call(&mut self, xt: Option<usize>, code: Rc<[Op<C>]>) -> Result {
+        let result = self.enter(xt, code).and_then(|()| self.run());
+        if result.is_err() {
+            self.backtrace = self
+                .returns
+                .iter()
+                .rev()
+                .filter_map(|frame| frame.xt)
+                .map(|xt| self.words[xt].name.to_string())
+                .collect();
+        }
+        self.returns.clear();
+        self.loops.clear();
+        result
+    }
+
+    /// The inner interpreter: step through ops until the return stack empties.
+    fn run(&mut self) -> Result {
+        while let Some(frame) = self.returns.last_mut() {
+            let op = frame.code[frame.ip];
+            frame.ip += 1;
+            self.tick()?;
+
+            match op {
+                Op::Call(xt) => {
+                    let Body::Colon(code) = &self.words[xt].body else {
+                        unreachable!("only colon definitions are called");
+                    };
+                    self.enter(Some(xt), Rc::clone(code))?;
+                }
+                Op::Exit => {
+                    self.returns.pop();
+                }
+                Op::Branch(target) => self.jump(target),
+                Op::ZeroBranch(target) => {
+                    if self.stack.pop()? == C::ZERO {
+                        self.jump(target);
+                    }
+                }
+                Op::Loop(body) => {
+                    let lp = self.loops.last_mut().expect("LOOP is compiled after DO");
+                    lp.index = lp.index.wrapping_add(C::ONE);
+                    if lp.index < lp.limit {
+                        self.jump(body);
+                    } else {
+                        self.loops.pop();
+                    }
+                }
+                _ => self.apply(op)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Continue the current word at `ip`.
+    fn jump(&mut self, ip: usize) {
+        self.returns
+            .last_mut()
+            .expect("branches run inside a frame")
+            .ip = ip;
+    }
+
+    /// Push a return frame for `code`, within the return stack limit.
+    ///
+    /// Only named words count towards the limit. An anonymous control
+    /// structure can only be at the bottom of the return stack.
+    fn enter(&mut self, xt: Option<usize>, code: Rc<[Op<C>]>) -> Result {
+        let anonymous = self.returns.first().is_some_and(|frame| frame.xt.is_none());
+        let depth = self.returns.len() - usize::from(anonymous);
+        if xt.is_some() && depth >= self.limits.return_depth {
+            return Err(Error::ReturnStackOverflow);
+        }
+        self.returns.push(Frame { xt, code, ip: 0 });
+        Ok(())
+    }
+
+    /// Count one more instruction against the step limit.
+    fn tick(&mut self) -> Result {
+        self.steps += 1;
+        if self.steps > self.limits.steps {
+            return Err(Error::StepLimitExceeded);
+        }
+        Ok(())
+    }
+
+    /// Execute a single op outside the inner interpreter.
+    fn step(&mut self, op: Op<C>) -> Result {
+        self.tick()?;
+        self.apply(op)
+    }
+
+    /// Apply a single non-control op, then check the data stack limit.
+    fn apply(&mut self, op: Op<C>) -> Result {
+        self.apply_unchecked(op)?;
+        if self.stack.len() > self.limits.stack_depth {
+            return Err(Error::DataStackOverflow);
+        }
+        Ok(())
+    }
+
+    fn apply_unchecked(&mut self, op: Op<C>) -> Result {
+        match op {
+            Op::Lit(n) => self.stack.push(n),
+            Op::Add => self.arithmetic(C::wrapping_add, C::checked_add)?,
+            Op::Sub => self.arithmetic(C::wrapping_sub, C::checked_sub)?,
+            Op::Mul => self.arithmetic(C::wrapping_mul, C::checked_mul)?,
+            Op::Div => {
+                if self.stack.len() >= 2 && self.stack.peek()? == C::ZERO {
+                    return Err(Error::DivisionByZero);
+                }
+                self.arithmetic(C::wrapping_div, C::checked_div)?
+            }
+            Op::Dup => {
+                let a = self.pop()?;
+                self.stack.push(a);
+                self.stack.push(a);
+            }
+            Op::Drop => {
+                self.pop()?;
+            }
+            Op::Swap => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(b);
+                self.stack.push(a);
+            }
+            Op::Over => {
+                let b = self.pop()?;
+                let a = self.pop()?;
+                self.stack.push(a);
+                self.stack.push(b);
+                self.stack.push(a);
+            }
+            Op::Eq => self.binary(|a, b| Ok(flag(a == b)))?,
+            Op::Lt => self.binary(|a, b| Ok(flag(a < b)))?,
+            Op::Gt => self.binary(|a, b| Ok(flag(a > b)))?,
+            Op::ZeroEq => {
+                let a = self.pop()?;
+                self.stack.push(flag(a == C::ZERO));
+            }
+            Op::I | Op::J => {
+                let depth = if op == Op::I { 1 } else { 2 };
+                let lp = self
+                    .loops
+                    .len()
+                    .checked_sub(depth)
+                    .ok_or(Error::InvalidWord)?;
+                self.stack.push(self.loops[lp].index);
+            }
+            Op::Do => {
+                let index = self.pop()?;
+                let limit = self.pop()?;
+                self.loops.push(LoopFrame { index, limit });
+            }
+            Op::Fetch => {
+                let address = self.address()?;
+                let value = *self.cell(address);
+                self.stack.push(value);
+            }
+            Op::Store => {
+                let address = self.address()?;
+                let value = self.pop()?;
+                *self.cell(address) = value;
+            }
+            Op::PlusStore => {
+                let address = self.address()?;
+                let n = self.pop()?;
+                let cell = *self.cell(address);
+                *self.cell(address) = if self.checked {
+                    cell.checked_add(n).ok_or(Error::Overflow)?
+                } else {
+                    cell.wrapping_add(n)
+                };
+            }
+            Op::Here => {
+                let here = Self::cell_address(self.memory.len())?;
+                self.stack.push(here);
+            }
+            Op::Allot => {
+                let n = self.pop()?;
+                self.allot(n)?;
+            }
+            Op::Comma => {
+                let n = self.pop()?;
+                self.allot(C::ONE)?;
+                *self.memory.last_mut().expect("a cell was just allotted") = n;
+            }
+            Op::Dot => {
+                let n = self.pop()?;
+                write!(self.output, "{n} ")?;
+            }
+            Op::Emit => {
+                let c = cell_char(self.pop()?);
+                write!(self.output, "{c}")?;
+            }
+            Op::Type => {
+                let range = self.range()?;
+                let text: String = range
+                    .map(|address| cell_char(*self.cell(address)))
+                    .collect();
+                write!(self.output, "{text}")?;
+            }
+            Op::Key => {
+                let key = match read_char(&mut self.input)? {
+                    Some(c) => char_cell(c),
+                    None => C::ZERO.wrapping_sub(C::ONE),
+                };
+                self.stack.push(key);
+            }
+            Op::Accept => {
+                let range = self.range()?;
+                let mut line = String::new();
+                self.input.read_line(&mut line)?;
+                let line = line.trim_end_matches(['\n', '\r']);
+
+                let mut len = 0;
+                for (address, c) in range.zip(line.chars()) {
+                    *self.cell(address) = char_cell(c);
+                    len += 1;
+                }
+                self.stack.push(Self::cell_address(len)?);
+            }
+            Op::Cr => writeln!(self.output)?,
+            Op::DotS => {
+                let stack = self.stack.as_slice();
+                write!(self.output, "<{}> ", stack.len())?;
+                for n in stack {
+                    write!(self.output, "{n} ")?;
+                }
+            }
+            Op::Words => {
+                let visible = self
+                    .words
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|&(xt, word)| self.names.get(&word.name) == Some(&xt));
+                for (_, word) in visible {
+                    write!(self.output, "{} ", word.name)?;
+                }
+                writeln!(self.output)?;
+            }
+            Op::Native(xt) => {
+                let Body::Native(f) = &self.words[xt].body else {
+                    unreachable!("NATIVE is only compiled for native words");
+                };
+                Rc::clone(f)(&mut self.stack)?;
+            }
+            Op::Call(_) | Op::Exit | Op::Branch(_) | Op::ZeroBranch(_) | Op::Loop(_) => {
+                unreachable!("control ops are handled by run")
+            }
+        }
+        Ok(())
+    }
+
+    fn binary<F>(&mut self, f: F) -> Result
+    where
+        F: FnOnce(C, C) -> std::result::Result<C, Error>,
+    {
+        let b = self.pop()?;
+        let a = self.pop()?;
+        self.stack.push(f(a, b)?);
+        Ok(())
+    }
+
+    /// Apply `wrapping`, or `checked` if the interpreter uses checked
+    /// arithmetic, to the top two cells.
+    fn arithmetic(&mut self, wrapping: fn(C, C) -> C, checked: fn(C, C) -> Option<C>) -> Result {
+        if self.checked {
+            self.binary(|a, b| checked(a, b).ok_or(Error::Overflow))
+        } else {
+            self.binary(|a, b| Ok(wrapping(a, b)))
+        }
+    }
+
+    fn pop(&mut self) -> std::result::Result<C, Error> {
+        self.stack.pop()
+    }
+
+    /// Convert a data space address to a cell.
+    fn cell_address(address: usize) -> std::result::Result<C, Error> {
+        C::from_usize(address).ok_or(Error::InvalidAddress)
+    }
+
+    /// Pop an address and a length, and check that every cell in between has
+    /// been allotted or is in the transient buffer.
+    fn range(&mut self) -> std::result::Result<std::ops::Range<usize>, Error> {
+        let len = self.pop()?;
+        let address = self.pop()?;
+        let range = address
+            .to_isize()
+            .zip(len.to_isize())
+            .and_then(|(address, len)| {
+                let start = usize::try_from(address).ok()?;
+                Some(start..start.checked_add(usize::try_from(len).ok()?)?)
+            })
+            .filter(|range| range.end <= self.memory.len() + self.transient.len())
+            .ok_or(Error::InvalidAddress)?;
+        Ok(range)
+    }
+
+    /// Pop an address and check that it refers to an allotted cell or one in
+    /// the transient buffer.
+    fn address(&mut self) -> std::result::Result<usize, Error> {
+        self.pop()?
+            .to_isize()
+            .and_then(|address| usize::try_from(address).ok())
+            .filter(|&address| address < self.memory.len() + self.transient.len())
+            .ok_or(Error::InvalidAddress)
+    }
+
+    /// The cell at an address checked by [`Forth::address`] or
+    /// [`Forth::range`], in the data space or the transient buffer after it.
+    fn cell(&mut self, address: usize) -> &mut C {
+        match address.checked_sub(self.memory.len()) {
+            Some(offset) => &mut self.transient[offset],
+            None => &mut self.memory[address],
+        }
+    }
+}
+
+/// The bytes a user definition takes up in the dictionary.
+fn word_bytes<C: Cell>(name: &str, body: &Body<C>) -> usize {
+    let code_len = match body {
+        Body::Colon(code) => code.len(),
+        Body::Inline(_) | Body::Native(_) => 0,
+    };
+    std::mem::size_of::<Word<C>>() + name.len() + code_len * std::mem::size_of::<Op<C>>()
+}
+
+/// Convert a character to the cell holding its code point.
+fn char_cell<C: Cell>(c: char) -> C {
+    C::from_usize(c as usize).expect("cells can hold any code point")
+}
+
+/// Convert a cell to the character with that code point, if there is one.
+fn cell_char<C: Cell>(cell: C) -> char {
+    cell.to_isize()
+        .and_then(|c| u32::try_from(c).ok())
+        .and_then(char::from_u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// Read one UTF-8 encoded character, or `None` at end of input.
+fn read_char(input: &mut dyn io::BufRead) -> io::Result<Option<char>> {
+    let mut bytes = [0; 4];
+    if input.read(&mut bytes[..1])? == 0 {
+        return Ok(None);
+    }
+    let len = match bytes[0] {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    };
+    input.read_exact(&mut bytes[1..len])?;
+    let c = std::str::from_utf8(&bytes[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .unwrap_or(char::REPLACEMENT_CHARACTER);
+    Ok(Some(c))
+}
+
+/// Convert a boolean to a Forth flag: all bits set for true, zero for false.
+fn flag<C: Cell>(b: bool) -> C {
+    if b {
+        C::TRUE
+    } else {
+        C::ZERO
     }
 }
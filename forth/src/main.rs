@@ -0,0 +1,56 @@
+//! An interactive Forth prompt.
+//!
+//! Usage: `forth [SCRIPT]...`
+//!
+//! Each script is evaluated in order before the prompt starts, so a session
+//! can begin from a prepared dictionary. Every line entered at the prompt is
+//! evaluated and answered with `ok` or the error it raised, along with where
+//! in the line it happened.
+
+use forth::Forth;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut forth = Forth::new();
+
+    for path in std::env::args().skip(1) {
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| {
+                forth
+                    .eval_with_diagnostics(&source)
+                    .map_err(|d| d.to_string())
+            });
+        if let Err(e) = loaded {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    loop {
+        match editor.readline("") {
+            Ok(line) => {
+                let _ = editor.add_history_entry(&line);
+                match forth.eval_with_diagnostics(&line) {
+                    Ok(()) => println!(" ok"),
+                    Err(e) => println!(" {e}"),
+                }
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => return ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{Cell, Value};
+use std::fmt;
+
+/// A single instruction of a compiled word.
+///
+/// Calls refer to other words by their index in the dictionary, so a
+/// definition only ever stores references to the words it was compiled
+/// against, never their expansion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op<C = Value> {
+    Lit(C),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Dup,
+    Drop,
+    Swap,
+    Over,
+    Eq,
+    Lt,
+    Gt,
+    ZeroEq,
+    /// Push the index of the innermost loop.
+    I,
+    /// Push the index of the next outer loop.
+    J,
+    Fetch,
+    Store,
+    PlusStore,
+    Here,
+    Allot,
+    Comma,
+    Dot,
+    Emit,
+    Cr,
+    DotS,
+    Words,
+    Type,
+    Key,
+    Accept,
+    Call(usize),
+    /// Call the native word with the given dictionary index.
+    Native(usize),
+    Exit,
+    /// Jump to the given ip.
+    Branch(usize),
+    /// Pop a flag and jump to the given ip if it is zero.
+    ZeroBranch(usize),
+    /// Pop a start index and a limit and enter a counted loop.
+    Do,
+    /// Step the innermost loop, jumping back to the given ip unless it is done.
+    Loop(usize),
+}
+
+impl<C: Cell> fmt::Display for Op<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Lit(n) => write!(f, "LIT {n}"),
+            Op::Add => write!(f, "+"),
+            Op::Sub => write!(f, "-"),
+            Op::Mul => write!(f, "*"),
+            Op::Div => write!(f, "/"),
+            Op::Dup => write!(f, "DUP"),
+            Op::Drop => write!(f, "DROP"),
+            Op::Swap => write!(f, "SWAP"),
+            Op::Over => write!(f, "OVER"),
+            Op::Eq => write!(f, "="),
+            Op::Lt => write!(f, "<"),
+            Op::Gt => write!(f, ">"),
+            Op::ZeroEq => write!(f, "0="),
+            Op::I => write!(f, "I"),
+            Op::J => write!(f, "J"),
+            Op::Fetch => write!(f, "@"),
+            Op::Store => write!(f, "!"),
+            Op::PlusStore => write!(f, "+!"),
+            Op::Here => write!(f, "HERE"),
+            Op::Allot => write!(f, "ALLOT"),
+            Op::Comma => write!(f, ","),
+            Op::Dot => write!(f, "."),
+            Op::Emit => write!(f, "EMIT"),
+            Op::Cr => write!(f, "CR"),
+            Op::DotS => write!(f, ".S"),
+            Op::Words => write!(f, "WORDS"),
+            Op::Type => write!(f, "TYPE"),
+            Op::Key => write!(f, "KEY"),
+            Op::Accept => write!(f, "ACCEPT"),
+            Op::Call(xt) => write!(f, "CALL #{xt}"),
+            Op::Native(xt) => write!(f, "NATIVE #{xt}"),
+            Op::Exit => write!(f, "EXIT"),
+            Op::Branch(ip) => write!(f, "BRANCH {ip}"),
+            Op::ZeroBranch(ip) => write!(f, "0BRANCH {ip}"),
+            Op::Do => write!(f, "DO"),
+            Op::Loop(ip) => write!(f, "LOOP {ip}"),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+/// Splits Forth source into whitespace-delimited tokens.
+///
+/// Unlike `str::split_whitespace`, the scanner is a cursor over the input, so
+/// parsing words can pick up where the outer interpreter left off, and the
+/// position of the last token is known when reporting an error.
+pub struct Scanner<'a> {
+    src: &'a str,
+    pos: usize,
+    /// Byte range of the token most recently returned.
+    last: (usize, usize),
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Scanner {
+            src,
+            pos: 0,
+            last: (0, 0),
+        }
+    }
+
+    /// Return the next whitespace-delimited token, or `None` at end of input.
+    pub fn next_token(&mut self) -> Option<&'a str> {
+        let rest = &self.src[self.pos..];
+        let start = self.pos + (rest.len() - rest.trim_start().len());
+        if start == self.src.len() {
+            self.pos = start;
+            return None;
+        }
+
+        let end = self.src[start..]
+            .find(char::is_whitespace)
+            .map_or(self.src.len(), |i| start + i);
+        self.pos = end;
+        self.last = (start, end);
+        Some(&self.src[start..end])
+    }
+
+    /// Return the raw text up to the next `delimiter`, skipping the single
+    /// space that separates it from the preceding token, or `None` if the
+    /// delimiter never appears.
+    pub fn parse_until(&mut self, delimiter: char) -> Option<&'a str> {
+        let mut start = self.pos;
+        if self.src[start..].starts_with(char::is_whitespace) {
+            start += self.src[start..].chars().next().map_or(0, char::len_utf8);
+        }
+        let end = start + self.src[start..].find(delimiter)?;
+        self.pos = end + delimiter.len_utf8();
+        Some(&self.src[start..end])
+    }
+
+    /// The token most recently returned, or `""` if there was none.
+    pub fn last_token(&self) -> &'a str {
+        &self.src[self.last.0..self.last.1]
+    }
+
+    /// The 1-based line and column, in characters, of the last token.
+    pub fn last_location(&self) -> (usize, usize) {
+        let before = &self.src[..self.last.0];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        let column = before[line_start..].chars().count() + 1;
+        (line, column)
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{Body, Cell, Error, Op, Stack, Value, Word};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The state of a [`Forth`](crate::Forth) session: its dictionary, data space
+/// and data stack.
+///
+/// Taken by [`Forth::snapshot`](crate::Forth::snapshot) and put back by
+/// [`Forth::restore`](crate::Forth::restore). Compiled definitions are shared
+/// between the interpreter and its snapshots rather than copied, so a
+/// snapshot costs a pointer per word plus a copy of the stack and data space.
+#[derive(Clone)]
+pub struct Snapshot<C: Cell = Value> {
+    pub(crate) stack: Stack<C>,
+    pub(crate) memory: Vec<C>,
+    pub(crate) words: Vec<Word<C>>,
+    pub(crate) names: HashMap<Rc<str>, usize>,
+    pub(crate) dictionary_bytes: usize,
+}
+
+/// Identifies a saved session, followed by the format version.
+const MAGIC: &[u8] = b"forth\0";
+const VERSION: u8 = 1;
+
+const LIT: u8 = 0;
+const CALL: u8 = 1;
+const NATIVE: u8 = 2;
+const BRANCH: u8 = 3;
+const ZERO_BRANCH: u8 = 4;
+const LOOP: u8 = 5;
+/// The tag of `OPERANDLESS[0]`; the rest follow in order.
+const OPERANDLESS_BASE: u8 = 16;
+
+const INLINE: u8 = 0;
+const COLON: u8 = 1;
+const NATIVE_WORD: u8 = 2;
+
+impl<C: Cell> Snapshot<C> {
+    /// Every op that is saved as just its tag. Only append to this list, so
+    /// that sessions saved by older versions keep their meaning.
+    const OPERANDLESS: [Op<C>; 30] = [
+        Op::Add,
+        Op::Sub,
+        Op::Mul,
+        Op::Div,
+        Op::Dup,
+        Op::Drop,
+        Op::Swap,
+        Op::Over,
+        Op::Eq,
+        Op::Lt,
+        Op::Gt,
+        Op::ZeroEq,
+        Op::I,
+        Op::J,
+        Op::Fetch,
+        Op::Store,
+        Op::PlusStore,
+        Op::Here,
+        Op::Allot,
+        Op::Comma,
+        Op::Dot,
+        Op::Emit,
+        Op::Cr,
+        Op::DotS,
+        Op::Words,
+        Op::Type,
+        Op::Key,
+        Op::Accept,
+        Op::Exit,
+        Op::Do,
+    ];
+
+    /// Encode the words after the first `builtins` of the dictionary, the
+    /// data space and the stack.
+    ///
+    /// Native words are saved by name only, since their code lives in the
+    /// host.
+    pub(crate) fn encode(&self, builtins: usize) -> Vec<u8> {
+        let mut out = Vec::from(MAGIC);
+        out.push(VERSION);
+        write_str(&mut out, C::NAME);
+
+        write_usize(&mut out, builtins);
+        write_usize(&mut out, self.words.len() - builtins);
+        for word in &self.words[builtins..] {
+            write_str(&mut out, &word.name);
+            match &word.body {
+                Body::Inline(op) => {
+                    out.push(INLINE);
+                    Self::write_op(&mut out, *op);
+                }
+                Body::Colon(code) => {
+                    out.push(COLON);
+                    write_usize(&mut out, code.len());
+                    for op in code.iter() {
+                        Self::write_op(&mut out, *op);
+                    }
+                }
+                Body::Native(_) => out.push(NATIVE_WORD),
+            }
+        }
+
+        for cells in [&self.memory[..], self.stack.as_slice()] {
+            write_usize(&mut out, cells.len());
+            for cell in cells {
+                out.extend(cell.to_bits().to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Decode a session saved by [`Snapshot::encode`] on top of `builtins`,
+    /// the built-in words it was saved without.
+    ///
+    /// `native` looks up the host's implementation of a native word by name.
+    /// The dictionary size is recomputed rather than trusted, and the code is
+    /// checked to be something the compiler could have produced.
+    pub(crate) fn decode(
+        bytes: &[u8],
+        builtins: &[Word<C>],
+        native: impl Fn(&str) -> Option<Body<C>>,
+    ) -> Result<Self, Error> {
+        let mut r = Reader(bytes);
+        if r.take(MAGIC.len())? != MAGIC || r.u8()? != VERSION || r.str()? != C::NAME {
+            return Err(Error::InvalidSnapshot);
+        }
+        if r.usize()? != builtins.len() {
+            return Err(Error::InvalidSnapshot);
+        }
+
+        let mut snapshot = Snapshot {
+            stack: Stack::default(),
+            memory: Vec::new(),
+            words: builtins.to_vec(),
+            names: HashMap::new(),
+            dictionary_bytes: 0,
+        };
+        for _ in 0..r.usize()? {
+            let name = r.str()?;
+            let body = match r.u8()? {
+                INLINE => Body::Inline(Self::read_op(&mut r)?),
+                COLON => {
+                    let code = (0..r.usize()?)
+                        .map(|_| Self::read_op(&mut r))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Body::Colon(code.into())
+                }
+                NATIVE_WORD => native(name).ok_or(Error::UnknownWord)?,
+                _ => return Err(Error::InvalidSnapshot),
+            };
+            snapshot.dictionary_bytes += crate::word_bytes(name, &body);
+            snapshot.words.push(Word {
+                name: name.into(),
+                body,
+            });
+        }
+
+        snapshot.memory = r.cells()?;
+        snapshot.dictionary_bytes += snapshot.memory.len() * std::mem::size_of::<C>();
+        for cell in r.cells()? {
+            snapshot.stack.push(cell);
+        }
+        if !r.0.is_empty() {
+            return Err(Error::InvalidSnapshot);
+        }
+
+        snapshot.verify()?;
+        for (xt, word) in snapshot.words.iter().enumerate() {
+            snapshot.names.insert(Rc::clone(&word.name), xt);
+        }
+        Ok(snapshot)
+    }
+
+    /// Check that the decoded code could have been compiled, so that running
+    /// it cannot trip up the inner interpreter.
+    fn verify(&self) -> Result<(), Error> {
+        let valid = self
+            .words
+            .iter()
+            .enumerate()
+            .all(|(xt, word)| match &word.body {
+                Body::Inline(op) => !matches!(
+                    op,
+                    Op::Call(_)
+                        | Op::Native(_)
+                        | Op::Exit
+                        | Op::Branch(_)
+                        | Op::ZeroBranch(_)
+                        | Op::Do
+                        | Op::Loop(_)
+                ),
+                Body::Colon(code) => self.verify_colon(xt, code),
+                Body::Native(_) => true,
+            });
+        if !valid {
+            return Err(Error::InvalidSnapshot);
+        }
+        Ok(())
+    }
+
+    /// Check the body of the colon definition `xt`: it only calls words of
+    /// the right kind defined before it, so it cannot recurse, its `DO`s and
+    /// `LOOP`s pair up, its branches stay within their definition and loop,
+    /// and it ends by returning.
+    fn verify_colon(&self, xt: usize, code: &[Op<C>]) -> bool {
+        let is = |callee: usize, colon: bool| {
+            callee < xt
+                && match self.words[callee].body {
+                    Body::Colon(_) => colon,
+                    Body::Native(_) => !colon,
+                    Body::Inline(_) => false,
+                }
+        };
+        // The innermost loop around each op, by the ip of the loop's body.
+        let mut regions = Vec::with_capacity(code.len());
+        let mut loops = Vec::new();
+        for (ip, op) in code.iter().enumerate() {
+            regions.push(loops.last().copied());
+            let valid = match *op {
+                Op::Call(callee) => is(callee, true),
+                Op::Native(callee) => is(callee, false),
+                Op::Exit => ip + 1 == code.len(),
+                Op::Do => {
+                    loops.push(ip + 1);
+                    true
+                }
+                Op::Loop(body) => loops.pop() == Some(body),
+                _ => true,
+            };
+            if !valid {
+                return false;
+            }
+        }
+        loops.is_empty()
+            && code.last() == Some(&Op::Exit)
+            && code.iter().enumerate().all(|(ip, op)| match *op {
+                Op::Branch(target) | Op::ZeroBranch(target) => {
+                    target < code.len() && regions[target] == regions[ip]
+                }
+                _ => true,
+            })
+    }
+
+    fn write_op(out: &mut Vec<u8>, op: Op<C>) {
+        let (tag, operand) = match op {
+            Op::Lit(n) => (LIT, n.to_bits()),
+            Op::Call(xt) => (CALL, xt as u64),
+            Op::Native(xt) => (NATIVE, xt as u64),
+            Op::Branch(ip) => (BRANCH, ip as u64),
+            Op::ZeroBranch(ip) => (ZERO_BRANCH, ip as u64),
+            Op::Loop(ip) => (LOOP, ip as u64),
+            _ => {
+                let i = Self::OPERANDLESS
+                    .iter()
+                    .position(|&other| other == op)
+                    .expect("every op without an operand is listed");
+                out.push(OPERANDLESS_BASE + i as u8);
+                return;
+            }
+        };
+        out.push(tag);
+        out.extend(operand.to_le_bytes());
+    }
+
+    fn read_op(r: &mut Reader) -> Result<Op<C>, Error> {
+        let tag = r.u8()?;
+        let op = match tag {
+            LIT => Op::Lit(C::from_bits(r.u64()?).ok_or(Error::InvalidSnapshot)?),
+            CALL => Op::Call(r.usize()?),
+            NATIVE => Op::Native(r.usize()?),
+            BRANCH => Op::Branch(r.usize()?),
+            ZERO_BRANCH => Op::ZeroBranch(r.usize()?),
+            LOOP => Op::Loop(r.usize()?),
+            _ => *tag
+                .checked_sub(OPERANDLESS_BASE)
+                .and_then(|i| Self::OPERANDLESS.get(usize::from(i)))
+                .ok_or(Error::InvalidSnapshot)?,
+        };
+        Ok(op)
+    }
+}
+
+fn write_usize(out: &mut Vec<u8>, n: usize) {
+    out.extend((n as u64).to_le_bytes());
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_usize(out, s.len());
+    out.extend(s.as_bytes());
+}
+
+/// A cursor over a saved session, failing with `Error::InvalidSnapshot` on
+/// truncated or out-of-range data.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if n > self.0.len() {
+            return Err(Error::InvalidSnapshot);
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.take(8)?.try_into().expect("took 8 bytes");
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn usize(&mut self) -> Result<usize, Error> {
+        usize::try_from(self.u64()?).map_err(|_| Error::InvalidSnapshot)
+    }
+
+    fn str(&mut self) -> Result<&'a str, Error> {
+        let len = self.usize()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| Error::InvalidSnapshot)
+    }
+
+    fn cells<C: Cell>(&mut self) -> Result<Vec<C>, Error> {
+        (0..self.usize()?)
+            .map(|_| C::from_bits(self.u64()?).ok_or(Error::InvalidSnapshot))
+            .collect()
+    }
+}
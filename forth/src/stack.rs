@@ -0,0 +1,42 @@
+use crate::{Cell, Error, Value};
+
+/// The data stack, as seen by native words.
+#[derive(Clone, Debug)]
+pub struct Stack<C = Value> {
+    cells: Vec<C>,
+}
+
+impl<C> Default for Stack<C> {
+    fn default() -> Self {
+        Stack { cells: Vec::new() }
+    }
+}
+
+impl<C: Cell> Stack<C> {
+    pub fn push(&mut self, value: C) {
+        self.cells.push(value);
+    }
+
+    /// Remove and return the top of the stack.
+    pub fn pop(&mut self) -> Result<C, Error> {
+        self.cells.pop().ok_or(Error::StackUnderflow)
+    }
+
+    /// Return the top of the stack without removing it.
+    pub fn peek(&self) -> Result<C, Error> {
+        self.cells.last().copied().ok_or(Error::StackUnderflow)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The stack contents, bottom first.
+    pub fn as_slice(&self) -> &[C] {
+        &self.cells
+    }
+}
@@ -0,0 +1,108 @@
+use forth::{Error, Forth, ForthBuilder, Stack};
+
+#[test]
+fn arithmetic_wraps_by_default() {
+    let mut f = Forth::new();
+    assert!(f.eval("2147483647 1 +").is_ok());
+    assert_eq!(vec![i32::MIN], f.stack());
+    assert!(f.eval("-1 /").is_ok());
+    assert_eq!(vec![i32::MIN], f.stack());
+}
+
+#[test]
+fn checked_arithmetic_reports_overflow() {
+    let mut f = ForthBuilder::new().checked_arithmetic(true).build();
+    assert_eq!(Err(Error::Overflow), f.eval("2147483647 1 +"));
+    assert_eq!(Err(Error::Overflow), f.eval("-2147483648 1 -"));
+    assert_eq!(Err(Error::Overflow), f.eval("65536 65536 *"));
+    assert_eq!(Err(Error::Overflow), f.eval("-2147483648 -1 /"));
+    assert_eq!(Err(Error::DivisionByZero), f.eval("1 0 /"));
+    assert!(f.eval("variable x 2147483647 x ! 0 x +!").is_ok());
+    assert_eq!(Err(Error::Overflow), f.eval("1 x +!"));
+}
+
+#[test]
+fn i64_cells_wrap_at_64_bits() {
+    let mut f = Forth::<i64>::default();
+    assert!(f.eval("4294967296 dup 2 / *").is_ok());
+    assert_eq!(&[1 << 63], f.stack());
+}
+
+#[test]
+fn i64_counters_in_memory() {
+    let mut f = Forth::<i64>::default();
+    assert!(f
+        .eval("variable big 3000000000 big ! 3000000000 big +! big @")
+        .is_ok());
+    assert_eq!(&[6_000_000_000], f.stack());
+    assert_eq!(&[6_000_000_000], f.memory());
+}
+
+#[test]
+fn i64_allot_beyond_what_can_be_allocated() {
+    let mut f = Forth::<i64>::default();
+    assert_eq!(
+        Err(Error::DictionaryFull),
+        f.eval("9223372036854775807 allot")
+    );
+    assert!(f.memory().is_empty());
+    assert!(f.eval("1 allot").is_ok());
+    assert_eq!(&[0], f.memory());
+}
+
+#[test]
+fn checked_i64_cells() {
+    let mut f = ForthBuilder::<i64>::default()
+        .checked_arithmetic(true)
+        .build();
+    assert!(f.eval("2147483647 1 +").is_ok());
+    assert_eq!(&[2_147_483_648], f.stack());
+    assert_eq!(Err(Error::Overflow), f.eval("9223372036854775807 1 +"));
+}
+
+#[test]
+fn f64_cells() {
+    let mut f = Forth::<f64>::default();
+    assert!(f.eval("1 3 / 1.5 2.5 * -1e3").is_ok());
+    assert_eq!(&[1.0 / 3.0, 3.75, -1000.0], f.stack());
+    assert_eq!(Err(Error::DivisionByZero), f.eval("1 0 /"));
+}
+
+#[test]
+fn f64_cells_with_control_flow_and_memory() {
+    let mut f = Forth::<f64>::default();
+    assert!(f
+        .eval("variable acc : sum 0 do i acc +! loop ; 4 sum acc @")
+        .is_ok());
+    assert_eq!(&[6.0], f.stack());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0.5 @"));
+}
+
+#[test]
+fn f64_names_that_look_like_floats() {
+    let mut f = Forth::<f64>::default();
+    assert!(f.eval(": inf 1e308 10 * ; inf").is_ok());
+    assert_eq!(&[f64::INFINITY], f.stack());
+    assert_eq!(Err(Error::InvalidWord), f.eval(": 1.5 2 ;"));
+}
+
+#[test]
+fn checked_f64_cells_reject_non_finite_results() {
+    let mut f = ForthBuilder::<f64>::default()
+        .checked_arithmetic(true)
+        .build();
+    assert_eq!(Err(Error::Overflow), f.eval("1e308 10 *"));
+}
+
+#[test]
+fn native_words_on_other_cells() {
+    let mut f = Forth::<f64>::default();
+    let sqrt = |stack: &mut Stack<f64>| {
+        let n = stack.pop()?;
+        stack.push(n.sqrt());
+        Ok(())
+    };
+    assert!(f.register_native("sqrt", sqrt).is_ok());
+    assert!(f.eval("2 sqrt").is_ok());
+    assert_eq!(&[std::f64::consts::SQRT_2], f.stack());
+}
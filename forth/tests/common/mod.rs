@@ -0,0 +1,32 @@
+use forth::Forth;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+/// An output sink the test can read back after handing it to the interpreter.
+#[derive(Clone, Default)]
+pub struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+    pub fn take(&self) -> String {
+        String::from_utf8(self.0.take()).unwrap()
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An interpreter writing to an [`Output`].
+pub fn forth() -> (Forth, Output) {
+    let mut f = Forth::new();
+    let output = Output::default();
+    f.set_output(output.clone());
+    (f, output)
+}
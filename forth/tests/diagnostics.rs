@@ -0,0 +1,81 @@
+use forth::{Diagnostic, Error, Forth};
+
+#[test]
+fn errors_compare_as_before() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 +"));
+    assert_eq!(Err(Error::UnknownWord), f.eval("foo"));
+}
+
+#[test]
+fn diagnostics_locate_the_failing_token() {
+    let mut f = Forth::new();
+    let d = f.eval_with_diagnostics("1 2 +\n  3 Foo 4").unwrap_err();
+    assert_eq!(
+        Diagnostic {
+            error: Error::UnknownWord,
+            token: "Foo".to_string(),
+            line: 2,
+            column: 5,
+            backtrace: vec![],
+        },
+        d
+    );
+}
+
+#[test]
+fn diagnostics_locate_errors_inside_definitions() {
+    let mut f = Forth::new();
+    let d = f
+        .eval_with_diagnostics(": foo\n  1 +\n  bar ;")
+        .unwrap_err();
+    assert_eq!(Error::UnknownWord, d.error);
+    assert_eq!(("bar", 3, 3), (d.token.as_str(), d.line, d.column));
+}
+
+#[test]
+fn diagnostics_count_columns_in_characters() {
+    let mut f = Forth::new();
+    let d = f.eval_with_diagnostics(": été 1 ; été drop /").unwrap_err();
+    assert_eq!(Error::StackUnderflow, d.error);
+    assert_eq!(("/", 1, 20), (d.token.as_str(), d.line, d.column));
+}
+
+#[test]
+fn backtrace_lists_executing_words_innermost_first() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": inner 0 / ; : middle 1 inner ; : outer middle ;")
+        .is_ok());
+    let d = f.eval_with_diagnostics("outer").unwrap_err();
+    assert_eq!(Error::DivisionByZero, d.error);
+    assert_eq!(vec!["inner", "middle", "outer"], d.backtrace);
+}
+
+#[test]
+fn backtrace_names_the_definition_that_ran() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo drop ; : bar foo ; : foo 1 ;").is_ok());
+    let d = f.eval_with_diagnostics("bar").unwrap_err();
+    assert_eq!(vec!["foo", "bar"], d.backtrace);
+}
+
+#[test]
+fn backtrace_is_reset_between_evals() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo drop ;").is_ok());
+    assert!(f.eval_with_diagnostics("foo").is_err());
+    let d = f.eval_with_diagnostics("drop").unwrap_err();
+    assert!(d.backtrace.is_empty());
+}
+
+#[test]
+fn diagnostics_display() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo drop ; : bar foo ;").is_ok());
+    let d = f.eval_with_diagnostics("1\nbar bar").unwrap_err();
+    assert_eq!(
+        "2:5: stack underflow `bar`\n    in foo\n    in bar",
+        d.to_string()
+    );
+}
@@ -1,4 +1,4 @@
-use forth::{Error, Forth, Value};
+use forth::{Error, Forth, Stack, Value};
 
 #[test]
 fn no_input_no_stack() {
@@ -371,3 +371,266 @@ fn redefine_an_existing_word_with_another_existing_word() {
     assert!(f.eval("bar foo").is_ok());
     assert_eq!(vec![6, 6], f.stack());
 }
+
+// Compiled words
+
+#[test]
+fn disassemble_shows_compiled_ops() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 5 ;").is_ok());
+    assert!(f.eval(": bar foo dup + ;").is_ok());
+    assert_eq!(
+        Ok("   0  LIT 5\n   1  EXIT\n".to_string()),
+        f.disassemble("foo")
+    );
+
+    let listing = f.disassemble("BAR").unwrap();
+    let ops: Vec<_> = listing.lines().map(str::trim).collect();
+    assert!(ops[0].starts_with("0  CALL foo#"));
+    assert_eq!(vec!["1  DUP", "2  +", "3  EXIT"], ops[1..]);
+}
+
+#[test]
+fn disassemble_keeps_the_snapshot_of_a_redefined_word() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 5 ; : bar foo ; : baz foo ;").is_ok());
+    assert!(f.eval(": foo 6 ; : qux foo ;").is_ok());
+    assert_eq!(f.disassemble("bar"), f.disassemble("baz"));
+    assert_ne!(f.disassemble("bar"), f.disassemble("qux"));
+}
+
+#[test]
+fn disassemble_unknown_word() {
+    assert_eq!(Err(Error::UnknownWord), Forth::new().disassemble("foo"));
+}
+
+#[test]
+fn deeply_nested_definitions_run() {
+    let mut f = Forth::new();
+    assert!(f.eval(": a 1 + ;").is_ok());
+    for (prev, next) in ('a'..'t').zip('b'..='t') {
+        assert!(f.eval(&format!(": {next} {prev} {prev} ;")).is_ok());
+    }
+    assert!(f.eval("0 t").is_ok());
+    assert_eq!(vec![1 << 19], f.stack());
+}
+
+// Control flow
+
+#[test]
+fn if_then() {
+    let mut f = Forth::new();
+    assert!(f.eval(": positive? 0 > if 1 then ;").is_ok());
+    assert!(f.eval("5 positive? -5 positive?").is_ok());
+    assert_eq!(vec![1], f.stack());
+}
+
+#[test]
+fn if_else_then() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": sign dup 0 < if drop -1 else 0 > if 1 else 0 then then ;")
+        .is_ok());
+    assert!(f.eval("-7 sign 0 sign 7 sign").is_ok());
+    assert_eq!(vec![-1, 0, 1], f.stack());
+}
+
+#[test]
+fn do_loop_with_index() {
+    let mut f = Forth::new();
+    assert!(f.eval(": sum-below 0 swap 0 do i + loop ;").is_ok());
+    assert!(f.eval("5 sum-below").is_ok());
+    assert_eq!(vec![10], f.stack());
+}
+
+#[test]
+fn nested_do_loops() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": pairs 3 1 do 3 1 do j 10 * i + loop loop ;")
+        .is_ok());
+    assert!(f.eval("pairs").is_ok());
+    assert_eq!(vec![11, 12, 21, 22], f.stack());
+}
+
+#[test]
+fn begin_until() {
+    let mut f = Forth::new();
+    assert!(f.eval(": countdown begin dup 1 - dup 0= until ;").is_ok());
+    assert!(f.eval("3 countdown").is_ok());
+    assert_eq!(vec![3, 2, 1, 0], f.stack());
+}
+
+#[test]
+fn begin_while_repeat() {
+    let mut f = Forth::new();
+    assert!(f.eval(": halve begin dup 1 > while 2 / repeat ;").is_ok());
+    assert!(f.eval("100 halve").is_ok());
+    assert_eq!(vec![1], f.stack());
+}
+
+#[test]
+fn control_flow_outside_a_definition() {
+    let mut f = Forth::new();
+    assert!(f.eval("1 if 2 else 3 then 4 0 do i loop").is_ok());
+    assert_eq!(vec![2, 0, 1, 2, 3], f.stack());
+}
+
+#[test]
+fn control_flow_respects_definition_snapshots() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 5 ; : bar 1 if foo then ; : foo 6 ;").is_ok());
+    assert!(f.eval("bar").is_ok());
+    assert_eq!(vec![5], f.stack());
+}
+
+#[test]
+fn unbalanced_control_flow() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::UnbalancedControlFlow), f.eval(": foo if ;"));
+    assert_eq!(Err(Error::UnbalancedControlFlow), f.eval(": foo then ;"));
+    assert_eq!(
+        Err(Error::UnbalancedControlFlow),
+        f.eval(": foo begin loop ;")
+    );
+    assert_eq!(
+        Err(Error::UnbalancedControlFlow),
+        f.eval(": foo do until ;")
+    );
+    assert_eq!(Err(Error::UnbalancedControlFlow), f.eval("1 if 2"));
+    assert_eq!(Err(Error::UnbalancedControlFlow), f.eval("repeat"));
+}
+
+#[test]
+fn control_words_cannot_be_redefined() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(": if 1 ;"));
+}
+
+// Memory
+
+#[test]
+fn variables_can_be_stored_and_fetched() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x variable y").is_ok());
+    assert!(f.eval("7 x ! 5 y ! x @ y @ -").is_ok());
+    assert_eq!(vec![2], f.stack());
+    assert_eq!(&[7, 5], f.memory());
+}
+
+#[test]
+fn plus_store_adds_to_a_cell() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable counter : tick 1 counter +! ;").is_ok());
+    assert!(f.eval("tick tick tick counter @").is_ok());
+    assert_eq!(vec![3], f.stack());
+}
+
+#[test]
+fn constants_push_their_value() {
+    let mut f = Forth::new();
+    assert!(f.eval("6 7 * constant answer : twice answer 2 * ;").is_ok());
+    assert!(f.eval("answer twice").is_ok());
+    assert_eq!(vec![42, 84], f.stack());
+}
+
+#[test]
+fn create_and_allot_reserve_cells() {
+    let mut f = Forth::new();
+    assert!(f.eval("variable x create buf 3 allot here").is_ok());
+    assert_eq!(vec![4], f.stack());
+    assert!(f.eval("drop 9 buf 2 + ! buf 2 + @").is_ok());
+    assert_eq!(vec![9], f.stack());
+    assert_eq!(&[0, 0, 0, 9], f.memory());
+}
+
+#[test]
+fn comma_appends_a_cell() {
+    let mut f = Forth::new();
+    assert!(f.eval("create primes 2 , 3 , 5 , primes 1 + @").is_ok());
+    assert_eq!(vec![3], f.stack());
+    assert_eq!(&[2, 3, 5], f.memory());
+}
+
+#[test]
+fn memory_access_is_bounds_checked() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 @"));
+    assert!(f.eval("variable x").is_ok());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("1 x 1 + !"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("-1 @"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("-2 allot"));
+}
+
+#[test]
+fn defining_words_cannot_be_compiled() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo variable x ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("variable"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("variable 1"));
+}
+
+// Native words
+
+fn square(stack: &mut Stack) -> Result<(), Error> {
+    let n = stack.pop()?;
+    stack.push(n * n);
+    Ok(())
+}
+
+#[test]
+fn native_words_can_be_called() {
+    let mut f = Forth::new();
+    assert!(f.register_native("square", square).is_ok());
+    assert!(f.eval("3 SQUARE : cube dup square * ; 2 cube").is_ok());
+    assert_eq!(vec![9, 8], f.stack());
+}
+
+#[test]
+fn native_words_can_capture_state() {
+    let mut f = Forth::new();
+    let offset = 100;
+    assert!(f
+        .register_native("offset", move |stack: &mut Stack| {
+            let n = stack.pop()?;
+            stack.push(n + offset);
+            Ok(())
+        })
+        .is_ok());
+    assert!(f.eval("1 offset").is_ok());
+    assert_eq!(vec![101], f.stack());
+}
+
+#[test]
+fn native_words_follow_redefinition_rules() {
+    let mut f = Forth::new();
+    assert!(f.register_native("dup", square).is_ok());
+    assert!(f.eval(": foo dup ;").is_ok());
+    assert!(f.eval(": dup 1 ;").is_ok());
+    assert!(f.eval("5 foo dup").is_ok());
+    assert_eq!(vec![25, 1], f.stack());
+    assert!(f.disassemble("foo").unwrap().contains("NATIVE dup#"));
+}
+
+#[test]
+fn native_errors_propagate() {
+    let mut f = Forth::new();
+    assert!(f
+        .register_native("fail", |_: &mut Stack| Err(Error::Native("no".to_string())))
+        .is_ok());
+    assert!(f.register_native("square", square).is_ok());
+    assert!(f.eval(": wrapped 1 fail ;").is_ok());
+    assert_eq!(Err(Error::Native("no".to_string())), f.eval("wrapped"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("drop drop square"));
+}
+
+#[test]
+fn native_words_need_a_valid_name() {
+    let mut f = Forth::new();
+    assert_eq!(Err(Error::InvalidWord), f.register_native("12", square));
+    assert_eq!(
+        Err(Error::InvalidWord),
+        f.register_native("two words", square)
+    );
+    assert_eq!(Err(Error::InvalidWord), f.register_native("if", square));
+}
@@ -0,0 +1,88 @@
+use forth::{Error, Forth, ForthBuilder, Stack};
+
+#[test]
+fn unlimited_by_default() {
+    let mut f = Forth::new();
+    assert!(f.eval(": big 10000 0 do i loop ; big").is_ok());
+    assert_eq!(10000, f.stack().len());
+}
+
+#[test]
+fn step_limit_stops_infinite_loops() {
+    let mut f = ForthBuilder::new().max_steps(100).build();
+    assert_eq!(
+        Err(Error::StepLimitExceeded),
+        f.eval(": spin begin 0 again ; spin")
+    );
+    assert_eq!(Err(Error::StepLimitExceeded), f.eval("begin 0 again"));
+}
+
+#[test]
+fn step_limit_applies_per_eval() {
+    let mut f = ForthBuilder::new().max_steps(3).build();
+    assert!(f.eval("1 2 +").is_ok());
+    assert!(f.eval("4 5 +").is_ok());
+    assert_eq!(Err(Error::StepLimitExceeded), f.eval("1 2 3 4"));
+    assert_eq!(vec![3, 9, 1, 2, 3], f.stack());
+}
+
+#[test]
+fn data_stack_depth_is_limited() {
+    let mut f = ForthBuilder::new().max_stack_depth(3).build();
+    assert!(f.eval("1 2 3").is_ok());
+    assert_eq!(Err(Error::DataStackOverflow), f.eval("dup"));
+
+    let mut f = ForthBuilder::new().max_stack_depth(3).build();
+    assert_eq!(
+        Err(Error::DataStackOverflow),
+        f.eval(": fill 10 0 do i loop ; fill")
+    );
+}
+
+#[test]
+fn data_stack_depth_covers_native_words() {
+    let mut f = Forth::builder().max_stack_depth(2).build();
+    let flood = |stack: &mut Stack| {
+        (0..5).for_each(|n| stack.push(n));
+        Ok(())
+    };
+    assert!(f.register_native("flood", flood).is_ok());
+    assert_eq!(Err(Error::DataStackOverflow), f.eval("flood"));
+}
+
+#[test]
+fn return_stack_depth_is_limited() {
+    let mut f = ForthBuilder::new().max_return_depth(3).build();
+    assert!(f.eval(": a 1 ; : b a ; : c b ; : d c ;").is_ok());
+    assert!(f.eval("c").is_ok());
+    assert_eq!(Err(Error::ReturnStackOverflow), f.eval("d"));
+    assert!(f.eval("c").is_ok());
+}
+
+#[test]
+fn control_structures_outside_definitions_are_not_calls() {
+    let mut f = ForthBuilder::new().max_return_depth(3).build();
+    assert!(f.eval(": a 1 ; : b a ; : c b ; : d c ;").is_ok());
+    assert!(f.eval("1 if c then").is_ok());
+    assert!(f.eval("2 0 do c loop").is_ok());
+    assert!(f.eval("begin c until").is_ok());
+    assert_eq!(Err(Error::ReturnStackOverflow), f.eval("1 if d then"));
+}
+
+#[test]
+fn dictionary_size_is_limited() {
+    let mut f = ForthBuilder::new().max_dictionary_bytes(1024).build();
+    assert!(f.eval(": small 1 ;").is_ok());
+    let long = format!(": long {} ;", "1 ".repeat(1024));
+    assert_eq!(Err(Error::DictionaryFull), f.eval(&long));
+    assert_eq!(Err(Error::UnknownWord), f.eval("long"));
+}
+
+#[test]
+fn data_space_counts_towards_the_dictionary() {
+    let mut f = ForthBuilder::new().max_dictionary_bytes(1024).build();
+    assert!(f.eval("create buf 10 allot").is_ok());
+    assert_eq!(Err(Error::DictionaryFull), f.eval("1000000 allot"));
+    assert_eq!(10, f.memory().len());
+    assert!(f.eval("-10 allot 100 allot").is_ok());
+}
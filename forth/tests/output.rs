@@ -0,0 +1,68 @@
+mod common;
+
+use common::forth;
+use forth::{Error, Forth};
+use std::io::{self, Write};
+
+#[test]
+fn dot_prints_and_pops() {
+    let (mut f, output) = forth();
+    assert!(f.eval("1 2 . .").is_ok());
+    assert_eq!("2 1 ", output.take());
+    assert!(f.stack().is_empty());
+    assert_eq!(Err(Error::StackUnderflow), f.eval("."));
+}
+
+#[test]
+fn emit_and_cr() {
+    let (mut f, output) = forth();
+    assert!(f.eval(": hi 72 emit 105 emit cr ; hi hi").is_ok());
+    assert_eq!("Hi\nHi\n", output.take());
+}
+
+#[test]
+fn dot_s_shows_the_stack_without_changing_it() {
+    let (mut f, output) = forth();
+    assert!(f.eval(".s 1 2 3 .s").is_ok());
+    assert_eq!("<0> <3> 1 2 3 ", output.take());
+    assert_eq!(vec![1, 2, 3], f.stack());
+}
+
+#[test]
+fn words_lists_visible_words_newest_first() {
+    let (mut f, output) = forth();
+    assert!(f.eval(": foo 1 ; : bar 2 ; : foo 3 ; words").is_ok());
+    let listing = output.take();
+    assert!(listing.starts_with("foo bar words .s cr "));
+    assert_eq!(1, listing.matches("foo").count());
+    assert!(listing.ends_with("- + \n"));
+}
+
+#[test]
+fn see_shows_the_compiled_definition() {
+    let (mut f, output) = forth();
+    assert!(f.eval(": foo 1 + ; see FOO").is_ok());
+    assert_eq!("   0  LIT 1\n   1  +\n   2  EXIT\n", output.take());
+    assert_eq!(Err(Error::UnknownWord), f.eval("see bar"));
+    assert_eq!(Err(Error::InvalidWord), f.eval("see"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": baz see foo ;"));
+}
+
+#[test]
+fn output_errors_are_reported() {
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut f = Forth::new();
+    f.set_output(Broken);
+    assert_eq!(Err(Error::Io(io::ErrorKind::BrokenPipe)), f.eval("1 ."));
+}
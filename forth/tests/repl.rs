@@ -0,0 +1,54 @@
+#![cfg(feature = "repl")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn repl(args: &[&str], input: &str) -> (bool, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_forth"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn answers_each_line() {
+    let (success, stdout, _) = repl(&[], "1 2 + .\nfoo\n.s\n");
+    assert!(success);
+    assert_eq!("3  ok\n 1:1: unknown word `foo`\n<0>  ok\n", stdout);
+}
+
+#[test]
+fn loads_scripts_before_the_prompt() {
+    let dir = std::env::temp_dir().join(format!("forth-repl-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = dir.join("square.fs");
+    std::fs::write(&script, ": square\n  dup *\n;\n").unwrap();
+
+    let (success, stdout, _) = repl(&[script.to_str().unwrap()], "7 square .\n");
+    assert!(success);
+    assert_eq!("49  ok\n", stdout);
+
+    std::fs::write(&script, ": half 2 / ;\n: broken\n  drop half ;\n1 broken").unwrap();
+    let (success, _, stderr) = repl(&[script.to_str().unwrap()], "");
+    assert!(!success);
+    assert!(
+        stderr.ends_with("square.fs: 4:3: stack underflow `broken`\n    in half\n    in broken\n")
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,242 @@
+use forth::{Error, Forth, ForthBuilder, Stack};
+
+#[test]
+fn restore_rolls_back_definitions_memory_and_stack() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; variable x 5 x ! 10").is_ok());
+    let snapshot = f.snapshot();
+
+    assert!(f.eval(": foo 2 ; : bar 3 ; 7 x ! 20 30").is_ok());
+    f.restore(&snapshot);
+    assert_eq!(&[10], f.stack());
+    assert_eq!(&[5], f.memory());
+    assert!(f.eval("foo x @").is_ok());
+    assert_eq!(&[10, 1, 5], f.stack());
+    assert_eq!(Err(Error::UnknownWord), f.eval("bar"));
+}
+
+#[test]
+fn a_snapshot_can_be_restored_more_than_once() {
+    let mut f = Forth::new();
+    assert!(f.eval("1").is_ok());
+    let snapshot = f.snapshot();
+    for _ in 0..3 {
+        assert!(f.eval("dup +").is_ok());
+        assert_eq!(&[2], f.stack());
+        f.restore(&snapshot);
+    }
+    assert_eq!(&[1], f.stack());
+}
+
+#[test]
+fn snapshots_are_not_affected_by_later_evaluation() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 ; : bar foo ;").is_ok());
+    let before = f.snapshot();
+    assert!(f.eval(": foo 2 ; : bar foo foo ;").is_ok());
+    let after = f.snapshot();
+
+    f.restore(&before);
+    assert!(f.eval("bar").is_ok());
+    assert_eq!(&[1], f.stack());
+    f.restore(&after);
+    assert!(f.eval("bar").is_ok());
+    assert_eq!(&[2, 2], f.stack());
+}
+
+#[test]
+fn restore_returns_dictionary_space() {
+    let mut f = ForthBuilder::new().max_dictionary_bytes(1_000).build();
+    let snapshot = f.snapshot();
+    assert!(f.eval("900 allot").is_err());
+    assert!(f.eval("200 allot").is_ok());
+    assert_eq!(Err(Error::DictionaryFull), f.eval("200 allot"));
+    f.restore(&snapshot);
+    assert!(f.eval("200 allot").is_ok());
+}
+
+#[test]
+fn save_and_load_resume_a_session() {
+    let mut f = Forth::new();
+    assert!(f
+        .eval(": square dup * ; variable x 7 x ! : sq-x x @ square ; 1 2 3")
+        .is_ok());
+    assert!(f
+        .eval("begin 1 until : loud if -1 else 1 then ; 3 0 do loop")
+        .is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut g = Forth::new();
+    assert!(g.load(&saved[..]).is_ok());
+    assert_eq!(f.stack(), g.stack());
+    assert_eq!(f.memory(), g.memory());
+    assert_eq!(f.disassemble("sq-x"), g.disassemble("sq-x"));
+    assert!(g.eval("sq-x 0 loud").is_ok());
+    assert_eq!(&[1, 2, 3, 49, 1], g.stack());
+}
+
+#[test]
+fn load_keeps_shadowed_definitions() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 5 ; : bar foo ; : foo 6 ;").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut g = Forth::new();
+    assert!(g.load(&saved[..]).is_ok());
+    assert!(g.eval("bar foo").is_ok());
+    assert_eq!(&[5, 6], g.stack());
+}
+
+#[test]
+fn load_replaces_the_current_session() {
+    let mut f = Forth::new();
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    assert!(f.eval(": foo 1 ; 2 3").is_ok());
+    assert!(f.load(&saved[..]).is_ok());
+    assert!(f.stack().is_empty());
+    assert_eq!(Err(Error::UnknownWord), f.eval("foo"));
+}
+
+#[test]
+fn load_resolves_native_words_by_name() {
+    fn triple(stack: &mut Stack) -> forth::Result {
+        let n = stack.pop()?;
+        stack.push(n * 3);
+        Ok(())
+    }
+
+    let mut f = Forth::new();
+    f.register_native("triple", triple).unwrap();
+    assert!(f.eval(": nine 3 triple ;").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut g = Forth::new();
+    assert_eq!(Err(Error::UnknownWord), g.load(&saved[..]));
+    g.register_native("triple", triple).unwrap();
+    assert!(g.load(&saved[..]).is_ok());
+    assert!(g.eval("nine 2 triple").is_ok());
+    assert_eq!(&[9, 6], g.stack());
+}
+
+#[test]
+fn load_rejects_malformed_data() {
+    let mut f = Forth::new();
+    assert!(f.eval(": foo 1 if 2 then ; 4").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut g = Forth::new();
+    assert_eq!(Err(Error::InvalidSnapshot), g.load(&b"not forth"[..]));
+    for len in 0..saved.len() {
+        assert_eq!(Err(Error::InvalidSnapshot), g.load(&saved[..len]));
+    }
+    let mut trailing = saved.clone();
+    trailing.push(0);
+    assert_eq!(Err(Error::InvalidSnapshot), g.load(&trailing[..]));
+    assert!(g.stack().is_empty());
+
+    // Code the compiler could not have produced, which would otherwise trip
+    // up the interpreter once run. Ops are saved as a tag, then any operand
+    // as eight bytes.
+    let (lit, call, zero_branch, dup, exit, op_do) = (0, 1, 4, 20, 44, 45);
+    let op = |tag: u8, operand: Option<u64>| {
+        let mut bytes = vec![tag];
+        bytes.extend(operand.into_iter().flat_map(u64::to_le_bytes));
+        bytes
+    };
+    for (source, from, to) in [
+        // A LOOP without its DO.
+        (": foo 3 0 do loop ;", op(op_do, None), op(dup, None)),
+        // A DO without its LOOP.
+        (": foo 3 0 do loop ;", op(5, Some(3)), op(dup, None)),
+        // A branch into a loop.
+        (
+            ": foo 0 if then 3 0 do loop ;",
+            op(zero_branch, Some(2)),
+            op(zero_branch, Some(5)),
+        ),
+        // A return before the end.
+        (": foo 77 2 ;", op(lit, Some(77)), op(exit, None)),
+        // Constants that are not a single value or primitive.
+        ("77 constant foo", op(lit, Some(77)), op(3, Some(0))),
+        ("77 constant foo", op(lit, Some(77)), op(op_do, None)),
+        ("77 constant foo", op(lit, Some(77)), op(exit, None)),
+        (
+            ": bar ; 77 constant foo",
+            op(lit, Some(77)),
+            op(call, Some(28)),
+        ),
+        // Calls to the word itself or a later word.
+        (
+            ": foo 1 ; : bar foo ;",
+            op(call, Some(28)),
+            op(call, Some(29)),
+        ),
+        (
+            ": foo 1 ; : bar foo ; : baz 2 ;",
+            op(call, Some(28)),
+            op(call, Some(30)),
+        ),
+    ] {
+        let mut f = Forth::new();
+        assert!(f.eval(source).is_ok());
+        let mut saved = Vec::new();
+        f.save(&mut saved).unwrap();
+        let found: Vec<_> = (0..saved.len())
+            .filter(|&at| saved[at..].starts_with(&from))
+            .collect();
+        let [at] = found[..] else {
+            panic!("{source}: {from:?} saved at {found:?}");
+        };
+        saved.splice(at..at + from.len(), to.iter().copied());
+        assert_eq!(
+            Err(Error::InvalidSnapshot),
+            g.load(&saved[..]),
+            "{source}: {to:?}"
+        );
+    }
+    assert!(g.stack().is_empty());
+}
+
+#[test]
+fn load_rejects_another_cell_type() {
+    let mut f = Forth::<i64>::default();
+    assert!(f.eval("1").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    assert_eq!(Err(Error::InvalidSnapshot), Forth::new().load(&saved[..]));
+    let mut g = Forth::<i64>::default();
+    assert!(g.load(&saved[..]).is_ok());
+    assert_eq!(&[1], g.stack());
+}
+
+#[test]
+fn load_respects_limits() {
+    let mut f = Forth::new();
+    assert!(f.eval("100 allot 1 2 3").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut small = ForthBuilder::new().max_dictionary_bytes(100).build();
+    assert_eq!(Err(Error::DictionaryFull), small.load(&saved[..]));
+    let mut shallow = ForthBuilder::new().max_stack_depth(2).build();
+    assert_eq!(Err(Error::DataStackOverflow), shallow.load(&saved[..]));
+}
+
+#[test]
+fn float_sessions_round_trip() {
+    let mut f = Forth::<f64>::default();
+    assert!(f.eval(": half 2 / ; 0.1 half -0.5").is_ok());
+    let mut saved = Vec::new();
+    f.save(&mut saved).unwrap();
+
+    let mut g = Forth::<f64>::default();
+    assert!(g.load(&saved[..]).is_ok());
+    assert_eq!(&[0.05, -0.5], g.stack());
+}
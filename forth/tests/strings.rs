@@ -0,0 +1,142 @@
+mod common;
+
+use common::{forth, Output};
+use forth::{Error, ForthBuilder};
+
+#[test]
+fn dot_quote_prints_immediately() {
+    let (mut f, output) = forth();
+    assert!(f.eval(".\" hello,  world\" 1 .").is_ok());
+    assert_eq!("hello,  world1 ", output.take());
+    assert!(f.stack().is_empty());
+}
+
+#[test]
+fn dot_quote_outside_a_definition_takes_no_space() {
+    let mut f = ForthBuilder::new().max_dictionary_bytes(16).build();
+    let output = Output::default();
+    f.set_output(output.clone());
+    for _ in 0..100 {
+        assert!(f.eval(".\" hello\"").is_ok());
+    }
+    assert_eq!("hello".repeat(100), output.take());
+    assert!(f.memory().is_empty());
+}
+
+#[test]
+fn dot_quote_in_definition_prints_when_run() {
+    let (mut f, output) = forth();
+    assert!(f.eval(": greet .\" hi \" ;").is_ok());
+    assert_eq!("", output.take());
+    assert!(f.eval("greet greet").is_ok());
+    assert_eq!("hi hi ", output.take());
+}
+
+#[test]
+fn s_quote_pushes_address_and_length() {
+    let (mut f, output) = forth();
+    assert!(f.eval("s\" été\"").is_ok());
+    assert_eq!(&[0, 3], f.stack());
+    assert!(f.eval("type").is_ok());
+    assert_eq!("été", output.take());
+}
+
+#[test]
+fn s_quote_outside_a_definition_reuses_a_transient_buffer() {
+    let (mut f, output) = forth();
+    assert!(f.eval("variable x s\" abc\" s\" de\"").is_ok());
+    assert_eq!(&[1, 3, 1, 2], f.stack());
+    assert_eq!(&[0], f.memory());
+    assert!(f.eval("type").is_ok());
+    assert_eq!("de", output.take());
+    assert!(f.eval("drop @ 1 + emit").is_ok());
+    assert_eq!("e", output.take());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("s\" ab\" + @"));
+}
+
+#[test]
+fn s_quote_in_definition_is_stored_once() {
+    let (mut f, output) = forth();
+    assert!(f.eval(": name s\" forth\" ; name type name type").is_ok());
+    assert_eq!("forthforth", output.take());
+    assert_eq!(5, f.memory().len());
+}
+
+#[test]
+fn strings_work_inside_top_level_control_flow() {
+    let (mut f, output) = forth();
+    assert!(f.eval("3 0 do .\" *\" loop").is_ok());
+    assert_eq!("***", output.take());
+}
+
+#[test]
+fn unterminated_string_is_an_error() {
+    let (mut f, _) = forth();
+    assert_eq!(Err(Error::InvalidWord), f.eval(".\" oops"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": foo s\" oops ;"));
+}
+
+#[test]
+fn string_words_cannot_be_redefined() {
+    let (mut f, _) = forth();
+    assert_eq!(Err(Error::InvalidWord), f.eval(": .\" 1 ;"));
+    assert_eq!(Err(Error::InvalidWord), f.eval(": S\" 1 ;"));
+}
+
+#[test]
+fn compiled_strings_count_against_the_dictionary() {
+    let mut f = ForthBuilder::new().max_dictionary_bytes(16).build();
+    assert_eq!(
+        Err(Error::DictionaryFull),
+        f.eval(": long .\" longer than four cells\" ;")
+    );
+}
+
+#[test]
+fn type_checks_bounds() {
+    let (mut f, _) = forth();
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 1 type"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("s\" ab\" 1 + type"));
+    assert_eq!(Err(Error::InvalidAddress), f.eval("0 -1 type"));
+    assert_eq!(Err(Error::StackUnderflow), f.eval("1 type"));
+}
+
+#[test]
+fn emit_prints_characters() {
+    let (mut f, output) = forth();
+    assert!(f.eval("72 emit 105 emit 233 emit").is_ok());
+    assert_eq!("Hié", output.take());
+}
+
+#[test]
+fn key_reads_characters_until_end_of_input() {
+    let (mut f, _) = forth();
+    f.set_input("aé".as_bytes());
+    assert!(f.eval("key key key").is_ok());
+    assert_eq!(&['a' as i32, 'é' as i32, -1], f.stack());
+}
+
+#[test]
+fn accept_reads_a_line() {
+    let (mut f, output) = forth();
+    f.set_input("hello\nworld\r\n".as_bytes());
+    assert!(f.eval("create buf 10 allot buf 10 accept").is_ok());
+    assert_eq!(&[5], f.stack());
+    assert!(f.eval("buf swap type").is_ok());
+    assert_eq!("hello", output.take());
+    assert!(f.eval("buf 10 accept buf swap type").is_ok());
+    assert_eq!("world", output.take());
+    assert!(f.eval("buf 10 accept").is_ok());
+    assert_eq!(&[0], f.stack());
+}
+
+#[test]
+fn accept_truncates_to_the_buffer() {
+    let (mut f, output) = forth();
+    f.set_input("truncated\n".as_bytes());
+    assert!(f
+        .eval("create buf 3 allot buf 3 accept buf swap type")
+        .is_ok());
+    assert_eq!("tru", output.take());
+    assert_eq!(Err(Error::InvalidAddress), f.eval("buf 4 accept"));
+}
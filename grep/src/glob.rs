@@ -0,0 +1,90 @@
+//! Shell-style globs, for `--include`, `--exclude` and `.gitignore` files.
+//!
+//! A glob is translated into an extended regular expression matched against
+//! a whole path: `*` matches any run of characters other than `/`, `?` any
+//! one character other than `/`, `**` any run of characters at all, `**/`
+//! any number of directories, and `[...]` a bracket expression, negated with
+//! `!` or `^`. A backslash makes the next character ordinary.
+
+use crate::regex::{Options, Regex};
+use crate::PatternError;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug)]
+pub(crate) struct Glob {
+    regex: Regex,
+}
+
+impl Glob {
+    pub(crate) fn new(glob: &str) -> Result<Self, PatternError> {
+        let options = Options {
+            extended: true,
+            ignore_case: false,
+            whole_line: true,
+        };
+        Ok(Glob {
+            regex: Regex::new(&translate(glob), options)?,
+        })
+    }
+
+    pub(crate) fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path.as_bytes())
+    }
+}
+
+fn translate(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    regex.push_str("(.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => match bracket(chars.clone()) {
+                Some((translated, rest)) => {
+                    regex.push_str(&translated);
+                    chars = rest;
+                }
+                None => push_literal(&mut regex, c),
+            },
+            '\\' => push_literal(&mut regex, chars.next().unwrap_or('\\')),
+            c => push_literal(&mut regex, c),
+        }
+    }
+    regex
+}
+
+/// Translate the bracket expression that follows a `[`, returning it with
+/// the rest of the glob, or None if it is not closed.
+fn bracket(mut chars: Peekable<Chars<'_>>) -> Option<(String, Peekable<Chars<'_>>)> {
+    let mut translated = String::from("[");
+    if chars.next_if(|&c| c == '!' || c == '^').is_some() {
+        translated.push('^');
+    }
+    // A `]` right after the opening bracket is an ordinary character.
+    if chars.next_if_eq(&']').is_some() {
+        translated.push(']');
+    }
+    loop {
+        let c = chars.next()?;
+        translated.push(c);
+        if c == ']' {
+            return Some((translated, chars));
+        }
+    }
+}
+
+fn push_literal(regex: &mut String, c: char) {
+    if c.is_ascii_punctuation() {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
@@ -0,0 +1,192 @@
+//! Output as JSON lines, for `--json`, in the style of ripgrep's.
+//!
+//! Each line is one message, an object with a `type` and its `data`:
+//!
+//! - `begin`, with the `path` of a file, before its first match;
+//! - `match`, with the `path`, the selected line as `lines`, its
+//!   `line_number` and `absolute_offset`, and its `submatches`, each with
+//!   the text matched and its `start` and `end` in the line;
+//! - `context`, like `match` for a line of context, without submatches;
+//! - `end`, with the `path` and the `stats` of a file, after its last match;
+//! - `summary`, with the `stats` of the whole search, last.
+//!
+//! Files without matches get no messages. Paths and text are given as
+//! `{"text": "..."}` when they are valid UTF-8, and otherwise as
+//! `{"bytes": "..."}` with the bytes in base64. Lines are given without their
+//! line terminators.
+
+use crate::{ContextLine, Match, Matches};
+use anyhow::Error;
+use std::fmt::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Counts for a file, or for all of them.
+#[derive(Debug, Default)]
+struct Stats {
+    searches_with_match: usize,
+    matched_lines: usize,
+    matches: usize,
+}
+
+pub(crate) fn messages(matches: Matches) -> Result<Vec<String>, Error> {
+    let mut output = Vec::new();
+    let mut total = Stats::default();
+    // The file being output, and its counts so far.
+    let mut file: Option<(PathBuf, Stats)> = None;
+    for found in matches {
+        let found = found?;
+        if file.as_ref().is_none_or(|(path, _)| *path != found.file) {
+            if let Some((path, stats)) = file.take() {
+                output.push(end(&path, &stats, &mut total));
+            }
+            output.push(format!(
+                r#"{{"type":"begin","data":{{"path":{}}}}}"#,
+                path(&found.file)
+            ));
+            let stats = Stats {
+                searches_with_match: 1,
+                ..Stats::default()
+            };
+            file = Some((found.file.clone(), stats));
+        }
+        if let Some((_, stats)) = &mut file {
+            stats.matched_lines += 1;
+            stats.matches += found.spans.len();
+        }
+        output.extend(found.before.iter().map(|line| context(&found, line)));
+        output.push(selected(&found));
+        output.extend(found.after.iter().map(|line| context(&found, line)));
+    }
+    if let Some((path, stats)) = file {
+        output.push(end(&path, &stats, &mut total));
+    }
+    output.push(format!(
+        r#"{{"type":"summary","data":{{"stats":{}}}}}"#,
+        stats(&total)
+    ));
+    Ok(output)
+}
+
+fn selected(found: &Match) -> String {
+    line(
+        "match",
+        &found.file,
+        found.line_number,
+        found.byte_offset,
+        &found.line,
+        &found.spans,
+    )
+}
+
+fn context(found: &Match, context: &ContextLine) -> String {
+    line(
+        "context",
+        &found.file,
+        context.line_number,
+        context.byte_offset,
+        &context.line,
+        &[],
+    )
+}
+
+fn line(
+    kind: &str,
+    file: &Path,
+    line_number: usize,
+    byte_offset: usize,
+    line: &[u8],
+    spans: &[Range<usize>],
+) -> String {
+    let mut submatches = String::new();
+    for (index, span) in spans.iter().enumerate() {
+        if index > 0 {
+            submatches.push(',');
+        }
+        write!(
+            submatches,
+            r#"{{"match":{},"start":{},"end":{}}}"#,
+            data(&line[span.clone()]),
+            span.start,
+            span.end
+        )
+        .expect("writing to a String cannot fail");
+    }
+    format!(
+        r#"{{"type":"{kind}","data":{{"path":{},"lines":{},"line_number":{line_number},"absolute_offset":{byte_offset},"submatches":[{submatches}]}}}}"#,
+        path(file),
+        data(line)
+    )
+}
+
+/// The message ending a file, whose counts are added to `total`.
+fn end(file: &Path, file_stats: &Stats, total: &mut Stats) -> String {
+    total.searches_with_match += file_stats.searches_with_match;
+    total.matched_lines += file_stats.matched_lines;
+    total.matches += file_stats.matches;
+    format!(
+        r#"{{"type":"end","data":{{"path":{},"stats":{}}}}}"#,
+        path(file),
+        stats(file_stats)
+    )
+}
+
+fn stats(stats: &Stats) -> String {
+    format!(
+        r#"{{"searches_with_match":{},"matched_lines":{},"matches":{}}}"#,
+        stats.searches_with_match, stats.matched_lines, stats.matches
+    )
+}
+
+fn path(path: &Path) -> String {
+    data(path.as_os_str().as_encoded_bytes())
+}
+
+/// `bytes` as `{"text": ...}` if they are UTF-8, and `{"bytes": ...}` in
+/// base64 if not.
+fn data(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => format!(r#"{{"text":{}}}"#, string(text)),
+        Err(_) => format!(r#"{{"bytes":"{}"}}"#, base64(bytes)),
+    }
+}
+
+/// `text` as a JSON string.
+fn string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => {
+                write!(quoted, "\\u{:04x}", c as u32).expect("writing to a String cannot fail");
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// `bytes` in standard base64, with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| {
+            bits | (byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[((bits >> (18 - 6 * index)) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -1,4 +1,17 @@
-use anyhow::Error;
+use anyhow::{bail, Context, Error};
+use std::path::{Path, PathBuf};
+
+mod glob;
+mod json;
+mod regex;
+mod search;
+mod walk;
+
+pub use regex::PatternError;
+pub use search::{ContextLine, Match, Matches};
+
+use regex::Regex;
+use walk::Filters;
 
 /// While using `&[&str]` to handle flags is convenient for exercise purposes,
 /// and resembles the output of [`std::env::args`], in real-world projects it is
@@ -9,22 +22,259 @@ use anyhow::Error;
 /// [`structopt`] to handle argument parsing, and of course doing so is
 /// permitted in this exercise as well, though it may be somewhat overkill.
 ///
+/// The flags understood are:
+///
+/// - `-n` prefixes each line with its line number.
+/// - `-l` lists the files that have a matching line, rather than the lines.
+/// - `-i` ignores case.
+/// - `-v` selects the lines that do not match.
+/// - `-x` only matches whole lines.
+/// - `-E` reads the pattern as an extended regular expression, with
+///   character classes, alternation, anchors, repetition and groups, rather
+///   than as a plain string.
+/// - `-r` searches the files under any directories given, rather than
+///   failing on them. Files in `.gitignore` files, binary files, which
+///   contain a NUL byte, and `.git` directories are skipped. Files are
+///   searched in order of their paths.
+/// - `--include GLOB` or `--include=GLOB` only searches the files under a
+///   directory whose names match the glob. It can be given more than once.
+/// - `--exclude GLOB` or `--exclude=GLOB` skips the files under a
+///   directory whose names match the glob.
+/// - `-A N` or `-AN` also prints the `N` lines after each selected line.
+/// - `-B N` or `-BN` also prints the `N` lines before each selected line.
+/// - `-C N` or `-CN` does both, for `-A` and `-B` not given themselves.
+/// - `--json` outputs a JSON object per line for each match, line of
+///   context and file with a match, and one for the whole search, in the
+///   style of ripgrep's JSON lines output. It cannot be used with `-l`.
+///
+/// Context lines are marked by a `-` after the file name and line number,
+/// rather than the `:` of selected lines, and groups of lines that do not
+/// follow on from one another are separated by a `--` line.
+///
+/// Files named directly are always searched, whatever `-r` would skip.
+///
 /// [`clap`]: https://crates.io/crates/clap
 /// [`std::env::args`]: https://doc.rust-lang.org/std/env/fn.args.html
 /// [`structopt`]: https://crates.io/crates/structopt
-#[derive(Debug)]
-pub struct Flags;
+#[derive(Debug, Default)]
+pub struct Flags {
+    line_numbers: bool,
+    files_only: bool,
+    ignore_case: bool,
+    invert: bool,
+    whole_line: bool,
+    extended: bool,
+    recursive: bool,
+    json: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    after: Option<usize>,
+    before: Option<usize>,
+    context: usize,
+    /// What was wrong with the first flag that was not understood, which
+    /// [`grep`] reports.
+    invalid: Option<String>,
+}
 
 impl Flags {
     pub fn new(flags: &[&str]) -> Self {
-        todo!(
-            "Given the flags {flags:?} implement your own 'Flags' struct to handle flags-related logic"
-        );
+        let mut parsed = Flags::default();
+        let mut flags = flags.iter().copied();
+        while let Some(flag) = flags.next() {
+            let switch = match flag {
+                "-n" => &mut parsed.line_numbers,
+                "-l" => &mut parsed.files_only,
+                "-i" => &mut parsed.ignore_case,
+                "-v" => &mut parsed.invert,
+                "-x" => &mut parsed.whole_line,
+                "-E" => &mut parsed.extended,
+                "-r" => &mut parsed.recursive,
+                "--json" => &mut parsed.json,
+                _ => {
+                    let result = if let Some(glob) = option(flag, "--include", &mut flags) {
+                        glob.map(|glob| parsed.include.push(glob.to_string()))
+                    } else if let Some(glob) = option(flag, "--exclude", &mut flags) {
+                        glob.map(|glob| parsed.exclude.push(glob.to_string()))
+                    } else if let Some(lines) = option(flag, "-A", &mut flags) {
+                        lines
+                            .and_then(context)
+                            .map(|lines| parsed.after = Some(lines))
+                    } else if let Some(lines) = option(flag, "-B", &mut flags) {
+                        lines
+                            .and_then(context)
+                            .map(|lines| parsed.before = Some(lines))
+                    } else if let Some(lines) = option(flag, "-C", &mut flags) {
+                        lines.and_then(context).map(|lines| parsed.context = lines)
+                    } else {
+                        Err(format!("unknown flag '{flag}'"))
+                    };
+                    if let Err(message) = result {
+                        parsed.invalid.get_or_insert(message);
+                    }
+                    continue;
+                }
+            };
+            *switch = true;
+        }
+        parsed
+    }
+
+    fn regex_options(&self) -> regex::Options {
+        regex::Options {
+            extended: self.extended,
+            ignore_case: self.ignore_case,
+            whole_line: self.whole_line,
+        }
     }
+
+    fn after_context(&self) -> usize {
+        self.after.unwrap_or(self.context)
+    }
+
+    fn before_context(&self) -> usize {
+        self.before.unwrap_or(self.context)
+    }
+}
+
+/// The value of an option such as `--include` or `-A`, given either as the
+/// next flag or after an `=` for long options and straight after the name for
+/// short ones. Returns None if `flag` is not the option, and an error if the
+/// value is missing.
+fn option<'f>(
+    flag: &'f str,
+    name: &str,
+    flags: &mut impl Iterator<Item = &'f str>,
+) -> Option<Result<&'f str, String>> {
+    if flag == name {
+        return Some(flags.next().ok_or(format!("'{name}' needs a value")));
+    }
+    let value = flag.strip_prefix(name)?;
+    if name.starts_with("--") {
+        value.strip_prefix('=').map(Ok)
+    } else {
+        Some(Ok(value))
+    }
+}
+
+/// Parse the number of lines of context.
+fn context(lines: &str) -> Result<usize, String> {
+    lines
+        .parse()
+        .map_err(|_| format!("invalid number of context lines '{lines}'"))
+}
+
+/// A file to search.
+#[derive(Debug)]
+struct Source {
+    path: PathBuf,
+    /// Whether the file was found under a directory, rather than named.
+    found: bool,
+}
+
+/// Search for `pattern` in `files`, as [`grep`] does, giving the selected
+/// lines with where they are, what matched and their context rather than
+/// formatting them.
+pub fn search(pattern: &str, flags: &Flags, files: &[&str]) -> Result<Matches, Error> {
+    let (regex, sources) = prepare(pattern, flags, files)?;
+    Ok(Matches::new(regex, flags, sources))
 }
 
 pub fn grep(pattern: &str, flags: &Flags, files: &[&str]) -> Result<Vec<String>, Error> {
-    todo!(
-        "Search the files '{files:?}' for '{pattern}' pattern and save the matches in a vector. Your search logic should be aware of the given flags '{flags:?}'"
-    );
+    if flags.json && flags.files_only {
+        bail!("'--json' cannot be used with '-l'");
+    }
+    let (regex, sources) = prepare(pattern, flags, files)?;
+    if flags.json {
+        return json::messages(Matches::new(regex, flags, sources));
+    }
+    let show_names = sources.len() > 1 || sources.iter().any(|source| source.found);
+    let separate = flags.after_context() > 0 || flags.before_context() > 0;
+
+    let mut output = Vec::new();
+    // The file and number of the last line output.
+    let mut last: Option<(PathBuf, usize)> = None;
+    for found in Matches::new(regex, flags, sources) {
+        let found = found?;
+        let file = found.file.to_string_lossy();
+        if flags.files_only {
+            output.push(file.to_string());
+            continue;
+        }
+
+        let first = found
+            .before
+            .first()
+            .map_or(found.line_number, |line| line.line_number);
+        if separate
+            && last.is_some_and(|(last_file, last_number)| {
+                last_file != found.file || last_number + 1 < first
+            })
+        {
+            output.push("--".to_string());
+        }
+        let name = show_names.then_some(&*file);
+        let number = |line_number| flags.line_numbers.then_some(line_number);
+        for line in &found.before {
+            output.push(format(name, number(line.line_number), '-', &line.line));
+        }
+        output.push(format(name, number(found.line_number), ':', &found.line));
+        for line in &found.after {
+            output.push(format(name, number(line.line_number), '-', &line.line));
+        }
+        let last_number = found
+            .after
+            .last()
+            .map_or(found.line_number, |line| line.line_number);
+        last = Some((found.file.clone(), last_number));
+    }
+    Ok(output)
+}
+
+/// Check the flags, compile the pattern and find the files to search.
+fn prepare(pattern: &str, flags: &Flags, files: &[&str]) -> Result<(Regex, Vec<Source>), Error> {
+    if let Some(message) = &flags.invalid {
+        bail!("{message}");
+    }
+    let regex = Regex::new(pattern, flags.regex_options())
+        .with_context(|| format!("invalid pattern '{pattern}'"))?;
+    Ok((regex, sources(flags, files)?))
+}
+
+/// Format a line for output, after its file name and line number if given,
+/// each followed by `separator`.
+fn format(name: Option<&str>, number: Option<usize>, separator: char, line: &[u8]) -> String {
+    let mut formatted = String::new();
+    if let Some(name) = name {
+        formatted.push_str(name);
+        formatted.push(separator);
+    }
+    if let Some(number) = number {
+        formatted.push_str(&format!("{number}{separator}"));
+    }
+    formatted.push_str(&String::from_utf8_lossy(line));
+    formatted
+}
+
+/// The files to search: those named, with the files under any directories
+/// named in their place if searching recursively.
+fn sources(flags: &Flags, files: &[&str]) -> Result<Vec<Source>, Error> {
+    let filters = if flags.recursive {
+        Filters::new(&flags.include, &flags.exclude).context("invalid glob")?
+    } else {
+        Filters::default()
+    };
+    let mut sources = Vec::new();
+    for &file in files {
+        let path = Path::new(file);
+        if flags.recursive && path.is_dir() {
+            let found = walk::files(path, &filters)?;
+            sources.extend(found.into_iter().map(|path| Source { path, found: true }));
+        } else {
+            sources.push(Source {
+                path: path.to_path_buf(),
+                found: false,
+            });
+        }
+    }
+    Ok(sources)
 }
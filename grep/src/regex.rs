@@ -0,0 +1,787 @@
+//! The pattern matcher behind [`grep`](crate::grep).
+//!
+//! Patterns are parsed into a tree of [`Node`]s, compiled into a program for
+//! a Thompson NFA, and run by a Pike VM, which steps through all the NFA's
+//! threads in lockstep, one character at a time. Searching a line therefore
+//! takes time proportional to the length of the line times the size of the
+//! program, whatever the pattern: there is no backtracking to blow up.
+//!
+//! Without `-E` a pattern is a plain string, which is compiled the same way.
+//!
+//! Lines are searched as bytes. A byte that is not part of valid UTF-8 reads
+//! as U+FFFD, so it only matches `.` and negated classes.
+
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+/// How many times a `{n,m}` repetition can repeat at most.
+const MAX_REPEAT: u32 = 1000;
+/// How many instructions a compiled pattern can have at most.
+const MAX_PROGRAM: usize = 100_000;
+
+/// A pattern that could not be compiled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternError {
+    message: &'static str,
+    position: Option<usize>,
+}
+
+impl PatternError {
+    /// The byte offset in the pattern where the problem was found, if it is
+    /// at a particular place.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at offset {position} of the pattern", self.message),
+            None => f.write_str(self.message),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+/// How to read and match a pattern.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    /// Read the pattern as an extended regular expression, rather than as a
+    /// plain string.
+    pub(crate) extended: bool,
+    pub(crate) ignore_case: bool,
+    /// Only match whole lines.
+    pub(crate) whole_line: bool,
+}
+
+/// A compiled pattern.
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str, options: Options) -> Result<Self, PatternError> {
+        let mut node = if options.extended {
+            Parser::parse(pattern)?
+        } else {
+            Node::Concat(pattern.chars().map(Node::Literal).collect())
+        };
+        if options.whole_line {
+            node = Node::Concat(vec![Node::Look(Look::Start), node, Node::Look(Look::End)]);
+        }
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+            ignore_case: options.ignore_case,
+        };
+        compiler.compile(&node)?;
+        compiler.push(Inst::Match)?;
+        Ok(Regex {
+            program: compiler.program,
+            ignore_case: options.ignore_case,
+        })
+    }
+
+    /// Whether the pattern matches anywhere in `line`.
+    pub(crate) fn is_match(&self, line: &[u8]) -> bool {
+        self.search(line, 0, true).is_some()
+    }
+
+    /// The byte ranges of the matches in `line`, leftmost-longest and not
+    /// overlapping. An empty match right after another match is skipped.
+    pub(crate) fn find_all(&self, line: &[u8]) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = Vec::new();
+        let mut start = 0;
+        while start <= line.len() {
+            let Some((from, to)) = self.search(line, start, false) else {
+                break;
+            };
+            if from < to || spans.last().is_none_or(|last| last.end < from) {
+                spans.push(from..to);
+            }
+            start = if from < to {
+                to
+            } else {
+                to + decode(line, to).map_or(1, |(_, width)| width)
+            };
+        }
+        spans
+    }
+
+    /// Find the leftmost match that starts at or after `start`, and of the
+    /// matches that start there the longest, as POSIX prescribes. With
+    /// `earliest`, settle for whichever match is found first.
+    fn search(&self, line: &[u8], start: usize, earliest: bool) -> Option<(usize, usize)> {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut best: Option<(usize, usize)> = None;
+
+        let mut at = start;
+        let mut here = Position {
+            at,
+            end: line.len(),
+            before: char_before(line, at),
+            after: decode(line, at),
+        };
+        loop {
+            // Only start new threads until something matches: any match
+            // they could find would start further right.
+            if best.is_none() {
+                self.add(&mut current, 0, at, &here);
+            }
+            let following = here.after.map(|(c, width)| Position {
+                at: at + width,
+                end: line.len(),
+                before: Some(c),
+                after: decode(line, at + width),
+            });
+
+            for &(pc, thread_start) in &current.threads {
+                if best.is_some_and(|(best_start, _)| thread_start > best_start) {
+                    continue;
+                }
+                match &self.program[pc] {
+                    Inst::Match => {
+                        if earliest {
+                            return Some((thread_start, at));
+                        }
+                        let better = best.is_none_or(|(best_start, best_end)| {
+                            thread_start < best_start || at > best_end
+                        });
+                        if better {
+                            best = Some((thread_start, at));
+                        }
+                    }
+                    inst => {
+                        if let (Some((c, _)), Some(following)) = (here.after, &following) {
+                            if self.consumes(inst, c) {
+                                self.add(&mut next, pc + 1, thread_start, following);
+                            }
+                        }
+                    }
+                }
+            }
+
+            let Some(following) = following else {
+                break;
+            };
+            if next.threads.is_empty() && best.is_some() {
+                break;
+            }
+            mem::swap(&mut current, &mut next);
+            next.clear();
+            at = following.at;
+            here = following;
+        }
+        best
+    }
+
+    /// Add a thread at `pc` to `threads`, following jumps, splits and
+    /// assertions to the instructions that consume a character.
+    fn add(&self, threads: &mut Threads, pc: usize, start: usize, here: &Position) {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if threads.contains(pc) {
+                continue;
+            }
+            threads.insert(pc, start);
+            match self.program[pc] {
+                Inst::Jump(to) => stack.push(to),
+                Inst::Split(first, second) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                Inst::Look(look) if here.satisfies(look) => stack.push(pc + 1),
+                _ => {}
+            }
+        }
+    }
+
+    fn consumes(&self, inst: &Inst, c: char) -> bool {
+        match inst {
+            Inst::Char(expected) if self.ignore_case => fold(c) == *expected,
+            Inst::Char(expected) => c == *expected,
+            Inst::Any => true,
+            Inst::Class(class) => class.matches(c, self.ignore_case),
+            _ => false,
+        }
+    }
+}
+
+/// A parsed pattern.
+#[derive(Debug)]
+enum Node {
+    Empty,
+    Literal(char),
+    /// `.`
+    Any,
+    Class(Class),
+    Look(Look),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+/// A bracket expression, or a class escape such as `\w`.
+#[derive(Clone, Debug)]
+struct Class {
+    items: Vec<ClassItem>,
+    negated: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Named(Named),
+}
+
+/// The POSIX character classes, plus the word characters of `\w`.
+#[derive(Clone, Copy, Debug)]
+enum Named {
+    Alnum,
+    Alpha,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Word,
+    Xdigit,
+}
+
+impl Class {
+    fn named(named: Named, negated: bool) -> Self {
+        Class {
+            items: vec![ClassItem::Named(named)],
+            negated,
+        }
+    }
+
+    fn matches(&self, c: char, ignore_case: bool) -> bool {
+        let contains = |c: char| self.items.iter().any(|item| item.contains(c));
+        let found = contains(c) || (ignore_case && (contains(fold(c)) || contains(upper(c))));
+        found != self.negated
+    }
+}
+
+impl ClassItem {
+    fn contains(self, c: char) -> bool {
+        match self {
+            ClassItem::Range(low, high) => (low..=high).contains(&c),
+            ClassItem::Named(named) => named.contains(c),
+        }
+    }
+}
+
+impl Named {
+    fn from_name(name: &str) -> Option<Self> {
+        let named = match name {
+            "alnum" => Named::Alnum,
+            "alpha" => Named::Alpha,
+            "blank" => Named::Blank,
+            "cntrl" => Named::Cntrl,
+            "digit" => Named::Digit,
+            "graph" => Named::Graph,
+            "lower" => Named::Lower,
+            "print" => Named::Print,
+            "punct" => Named::Punct,
+            "space" => Named::Space,
+            "upper" => Named::Upper,
+            "xdigit" => Named::Xdigit,
+            _ => return None,
+        };
+        Some(named)
+    }
+
+    fn contains(self, c: char) -> bool {
+        match self {
+            Named::Alnum => c.is_alphanumeric(),
+            Named::Alpha => c.is_alphabetic(),
+            Named::Blank => c == ' ' || c == '\t',
+            Named::Cntrl => c.is_control(),
+            Named::Digit => c.is_ascii_digit(),
+            Named::Graph => !c.is_control() && !c.is_whitespace(),
+            Named::Lower => c.is_lowercase(),
+            Named::Print => !c.is_control(),
+            Named::Punct => c.is_ascii_punctuation(),
+            Named::Space => c.is_whitespace(),
+            Named::Upper => c.is_uppercase(),
+            Named::Word => is_word(c),
+            Named::Xdigit => c.is_ascii_hexdigit(),
+        }
+    }
+}
+
+/// A zero-width assertion.
+#[derive(Clone, Copy, Debug)]
+enum Look {
+    /// `^`
+    Start,
+    /// `$`
+    End,
+    /// `\b`
+    WordBoundary,
+    /// `\B`
+    NotWordBoundary,
+    /// `\<`
+    WordStart,
+    /// `\>`
+    WordEnd,
+}
+
+/// A recursive descent parser for extended regular expressions.
+struct Parser {
+    /// The pattern's characters, with their byte offsets.
+    chars: Vec<(usize, char)>,
+    /// The index in `chars` of the next character.
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(pattern: &str) -> Result<Node, PatternError> {
+        let mut parser = Parser {
+            chars: pattern.char_indices().collect(),
+            pos: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error_at(parser.pos, "unmatched ')'"));
+        }
+        Ok(node)
+    }
+
+    fn alternation(&mut self) -> Result<Node, PatternError> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().expect("just checked")
+        } else {
+            Node::Alternate(branches)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, PatternError> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            items.push(self.repetitions(atom)?);
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.pop().expect("just checked"),
+            _ => Node::Concat(items),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, PatternError> {
+        let start = self.pos;
+        let c = self.next().expect("concat checked for a character");
+        let node = match c {
+            '(' => {
+                let inner = self.alternation()?;
+                if !self.eat(')') {
+                    return Err(self.error_at(start, "unmatched '('"));
+                }
+                inner
+            }
+            '.' => Node::Any,
+            '^' => Node::Look(Look::Start),
+            '$' => Node::Look(Look::End),
+            '[' => self.bracket(start)?,
+            '\\' => self.escape(start)?,
+            '*' | '+' | '?' => return Err(self.error_at(start, "nothing to repeat")),
+            '{' => {
+                self.pos = start;
+                if self.bound()?.is_some() {
+                    return Err(self.error_at(start, "nothing to repeat"));
+                }
+                // A brace that does not start a bound is an ordinary character.
+                self.pos = start + 1;
+                Node::Literal('{')
+            }
+            c => Node::Literal(c),
+        };
+        Ok(node)
+    }
+
+    /// Apply any `*`, `+`, `?` and `{n,m}` that follow `node`.
+    fn repetitions(&mut self, mut node: Node) -> Result<Node, PatternError> {
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => match self.bound()? {
+                    Some(bound) => {
+                        node = Node::Repeat {
+                            node: Box::new(node),
+                            min: bound.0,
+                            max: bound.1,
+                        };
+                        continue;
+                    }
+                    None => return Ok(node),
+                },
+                _ => return Ok(node),
+            };
+            self.pos += 1;
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+            };
+        }
+    }
+
+    /// Parse a `{n}`, `{n,}`, `{,m}` or `{n,m}` bound at the current
+    /// position. If there is a brace that does not start one, the position is
+    /// left where it was and None is returned.
+    fn bound(&mut self) -> Result<Option<(u32, Option<u32>)>, PatternError> {
+        let start = self.pos;
+        self.pos += 1;
+        let min = self.number()?;
+        let max = if self.eat(',') { self.number()? } else { min };
+        let bound = match (min, max) {
+            (None, None) => None,
+            (min, max) if self.eat('}') => Some((min.unwrap_or(0), max)),
+            _ => None,
+        };
+        let Some((min, max)) = bound else {
+            self.pos = start;
+            return Ok(None);
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error_at(start, "invalid repetition bounds"));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn number(&mut self) -> Result<Option<u32>, PatternError> {
+        let start = self.pos;
+        let mut value: Option<u32> = None;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            self.pos += 1;
+            let n = value.unwrap_or(0) * 10 + digit;
+            if n > MAX_REPEAT {
+                return Err(self.error_at(start, "repetition count is too large"));
+            }
+            value = Some(n);
+        }
+        Ok(value)
+    }
+
+    /// Parse a bracket expression, whose `[` is at `start`.
+    fn bracket(&mut self, start: usize) -> Result<Node, PatternError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let item_start = self.pos;
+            let Some(c) = self.next() else {
+                return Err(self.error_at(start, "unterminated '['"));
+            };
+            // A `]` right after the opening bracket is an ordinary character.
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+
+            if c == '[' && self.eat(':') {
+                let name_start = self.pos;
+                while self.peek().is_some_and(|c| c != ':') {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[name_start..self.pos]
+                    .iter()
+                    .map(|&(_, c)| c)
+                    .collect();
+                if !(self.eat(':') && self.eat(']')) {
+                    return Err(self.error_at(item_start, "unterminated character class name"));
+                }
+                let named = Named::from_name(&name)
+                    .ok_or_else(|| self.error_at(item_start, "unknown character class"))?;
+                items.push(ClassItem::Named(named));
+                continue;
+            }
+
+            let is_range = self.peek() == Some('-')
+                && self.chars.get(self.pos + 1).is_some_and(|&(_, c)| c != ']');
+            if is_range {
+                self.pos += 1;
+                let high = self.next().expect("just checked");
+                if high < c {
+                    return Err(self.error_at(item_start, "invalid range"));
+                }
+                items.push(ClassItem::Range(c, high));
+            } else {
+                items.push(ClassItem::Range(c, c));
+            }
+        }
+        Ok(Node::Class(Class { items, negated }))
+    }
+
+    /// Parse the escape whose backslash is at `start`.
+    fn escape(&mut self, start: usize) -> Result<Node, PatternError> {
+        let Some(c) = self.next() else {
+            return Err(self.error_at(start, "trailing backslash"));
+        };
+        let node = match c {
+            'w' => Node::Class(Class::named(Named::Word, false)),
+            'W' => Node::Class(Class::named(Named::Word, true)),
+            's' => Node::Class(Class::named(Named::Space, false)),
+            'S' => Node::Class(Class::named(Named::Space, true)),
+            'd' => Node::Class(Class::named(Named::Digit, false)),
+            'D' => Node::Class(Class::named(Named::Digit, true)),
+            'b' => Node::Look(Look::WordBoundary),
+            'B' => Node::Look(Look::NotWordBoundary),
+            '<' => Node::Look(Look::WordStart),
+            '>' => Node::Look(Look::WordEnd),
+            '1'..='9' => return Err(self.error_at(start, "backreferences are not supported")),
+            c if c.is_alphanumeric() => return Err(self.error_at(start, "unknown escape")),
+            c => Node::Literal(c),
+        };
+        Ok(node)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|&(_, c)| c)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// An error about the character at index `pos` of `chars`.
+    fn error_at(&self, pos: usize, message: &'static str) -> PatternError {
+        let offset = self.chars.get(pos).map_or_else(
+            || self.chars.last().map_or(0, |&(i, c)| i + c.len_utf8()),
+            |&(i, _)| i,
+        );
+        PatternError {
+            message,
+            position: Some(offset),
+        }
+    }
+}
+
+/// An instruction of a compiled pattern.
+#[derive(Debug)]
+enum Inst {
+    /// Consume this character, which is case folded when ignoring case.
+    Char(char),
+    /// Consume any character.
+    Any,
+    Class(Class),
+    /// Continue only if the assertion holds.
+    Look(Look),
+    /// Continue at both instructions.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+    ignore_case: bool,
+}
+
+impl Compiler {
+    fn compile(&mut self, node: &Node) -> Result<(), PatternError> {
+        match node {
+            Node::Empty => {}
+            &Node::Literal(c) => {
+                let c = if self.ignore_case { fold(c) } else { c };
+                self.push(Inst::Char(c))?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Class(class) => {
+                self.push(Inst::Class(class.clone()))?;
+            }
+            &Node::Look(look) => {
+                self.push(Inst::Look(look))?;
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node)?;
+                }
+            }
+            Node::Alternate(branches) => {
+                let (last, rest) = branches.split_last().expect("at least two branches");
+                let mut jumps = Vec::new();
+                for branch in rest {
+                    let split = self.push(Inst::Split(0, 0))?;
+                    self.compile(branch)?;
+                    jumps.push(self.push(Inst::Jump(0))?);
+                    self.program[split] = Inst::Split(split + 1, self.program.len());
+                }
+                self.compile(last)?;
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(self.program.len());
+                }
+            }
+            Node::Repeat { node, min, max } => {
+                for _ in 0..*min {
+                    self.compile(node)?;
+                }
+                match *max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.compile(node)?;
+                        self.push(Inst::Jump(split))?;
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                    Some(max) => {
+                        for _ in *min..max {
+                            let split = self.push(Inst::Split(0, 0))?;
+                            self.compile(node)?;
+                            self.program[split] = Inst::Split(split + 1, self.program.len());
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Append an instruction, returning its index.
+    fn push(&mut self, inst: Inst) -> Result<usize, PatternError> {
+        if self.program.len() == MAX_PROGRAM {
+            return Err(PatternError {
+                message: "pattern is too large",
+                position: None,
+            });
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+}
+
+/// The threads of the Pike VM at one position: the instruction each is at,
+/// with the offset where its match started. At most one thread is kept per
+/// instruction; since threads at the same instruction and position behave
+/// the same from then on, the one that started earliest is the one to keep.
+struct Threads {
+    threads: Vec<(usize, usize)>,
+    /// For each instruction, its index in `threads` if it is there.
+    index: Vec<usize>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Threads {
+            threads: Vec::with_capacity(len),
+            index: vec![0; len],
+        }
+    }
+
+    fn contains(&self, pc: usize) -> bool {
+        let i = self.index[pc];
+        self.threads.get(i).is_some_and(|&(at, _)| at == pc)
+    }
+
+    fn insert(&mut self, pc: usize, start: usize) {
+        self.index[pc] = self.threads.len();
+        self.threads.push((pc, start));
+    }
+
+    fn clear(&mut self) {
+        self.threads.clear();
+    }
+}
+
+/// What assertions can see at a position in the line.
+struct Position {
+    at: usize,
+    end: usize,
+    before: Option<char>,
+    /// The character at the position, with its width in bytes.
+    after: Option<(char, usize)>,
+}
+
+impl Position {
+    fn satisfies(&self, look: Look) -> bool {
+        let word_before = self.before.is_some_and(is_word);
+        let word_after = self.after.is_some_and(|(c, _)| is_word(c));
+        match look {
+            Look::Start => self.at == 0,
+            Look::End => self.at == self.end,
+            Look::WordBoundary => word_before != word_after,
+            Look::NotWordBoundary => word_before == word_after,
+            Look::WordStart => !word_before && word_after,
+            Look::WordEnd => word_before && !word_after,
+        }
+    }
+}
+
+/// Decode the character at byte offset `at`, with its width.
+fn decode(line: &[u8], at: usize) -> Option<(char, usize)> {
+    let rest = line.get(at..).filter(|rest| !rest.is_empty())?;
+    let chunk = &rest[..rest.len().min(4)];
+    let valid = match std::str::from_utf8(chunk) {
+        Ok(valid) => valid,
+        Err(error) => std::str::from_utf8(&chunk[..error.valid_up_to()]).expect("valid up to"),
+    };
+    Some(match valid.chars().next() {
+        Some(c) => (c, c.len_utf8()),
+        None => (char::REPLACEMENT_CHARACTER, 1),
+    })
+}
+
+/// Decode the character that ends at byte offset `at`.
+fn char_before(line: &[u8], at: usize) -> Option<char> {
+    (1..=at.min(4))
+        .rev()
+        .find_map(|width| match decode(line, at - width) {
+            Some((c, decoded)) if decoded == width => Some(c),
+            _ => None,
+        })
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// The lower case of `c`, if it is a single character.
+fn fold(c: char) -> char {
+    single(c.to_lowercase()).unwrap_or(c)
+}
+
+/// The upper case of `c`, if it is a single character.
+fn upper(c: char) -> char {
+    single(c.to_uppercase()).unwrap_or(c)
+}
+
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
@@ -0,0 +1,207 @@
+//! The structured results of a search, which [`grep`](crate::grep) formats.
+
+use crate::regex::Regex;
+use crate::{Flags, Source};
+use anyhow::{Context, Error};
+use std::collections::VecDeque;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::vec;
+
+/// A selected line: one that matches, or with `-v` one that does not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    /// The file the line is in, as named or as found under a named
+    /// directory.
+    pub file: PathBuf,
+    /// The number of the line in the file, counting from 1.
+    pub line_number: usize,
+    /// The offset of the start of the line from the start of the file.
+    pub byte_offset: usize,
+    /// The line, without its line terminator.
+    pub line: Vec<u8>,
+    /// The byte ranges of the line that the pattern matches. Empty with `-v`.
+    pub spans: Vec<Range<usize>>,
+    /// The lines of context before the line, asked for with `-B` or `-C`.
+    /// Lines already given as context after the previous match are left out.
+    pub before: Vec<ContextLine>,
+    /// The lines of context after the line, asked for with `-A` or `-C`.
+    /// They stop short of the next match.
+    pub after: Vec<ContextLine>,
+}
+
+/// A line of context around a [`Match`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextLine {
+    pub line_number: usize,
+    pub byte_offset: usize,
+    pub line: Vec<u8>,
+}
+
+/// The matches of a search, file by file and line by line, returned by
+/// [`search`](crate::search).
+///
+/// Files are read one at a time as the iterator gets to them; a file that
+/// cannot be read is an error, after which the search goes on with the next
+/// file. With `-l`, only the first match of each file is given, without
+/// context.
+#[derive(Debug)]
+pub struct Matches {
+    searcher: Searcher,
+    sources: vec::IntoIter<Source>,
+    file: Option<File>,
+}
+
+/// What to look for in each file.
+#[derive(Debug)]
+struct Searcher {
+    regex: Regex,
+    invert: bool,
+    first_only: bool,
+    after_context: usize,
+    before_context: usize,
+}
+
+/// The file being searched.
+#[derive(Debug)]
+struct File {
+    path: PathBuf,
+    contents: Vec<u8>,
+    /// The offset of the next line to read.
+    offset: usize,
+    /// The number of the last line read.
+    line_number: usize,
+    /// The lines since the last one given, up to the before context.
+    before: VecDeque<ContextLine>,
+    /// The last match, while it waits for its after context.
+    pending: Option<Match>,
+    /// How many more lines of after context the pending match needs, never 0
+    /// while there is one.
+    after: usize,
+}
+
+impl Matches {
+    pub(crate) fn new(regex: Regex, flags: &Flags, sources: Vec<Source>) -> Self {
+        let context = |lines| if flags.files_only { 0 } else { lines };
+        Matches {
+            searcher: Searcher {
+                regex,
+                invert: flags.invert,
+                first_only: flags.files_only,
+                after_context: context(flags.after_context()),
+                before_context: context(flags.before_context()),
+            },
+            sources: sources.into_iter(),
+            file: None,
+        }
+    }
+}
+
+impl Iterator for Matches {
+    type Item = Result<Match, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(file) = &mut self.file else {
+                match File::open(self.sources.next()?) {
+                    Ok(file) => self.file = file,
+                    Err(error) => return Some(Err(error)),
+                }
+                continue;
+            };
+            if let Some(found) = file.next(&self.searcher) {
+                return Some(Ok(found));
+            }
+            self.file = None;
+        }
+    }
+}
+
+impl File {
+    /// Read the file to search, or None if it is a binary file that was not
+    /// named.
+    fn open(source: Source) -> Result<Option<Self>, Error> {
+        let contents = fs::read(&source.path)
+            .with_context(|| format!("cannot read '{}'", source.path.display()))?;
+        if source.found && contents.contains(&0) {
+            return Ok(None);
+        }
+        Ok(Some(File {
+            path: source.path,
+            contents,
+            offset: 0,
+            line_number: 0,
+            before: VecDeque::new(),
+            pending: None,
+            after: 0,
+        }))
+    }
+
+    /// The next match in the file, once its after context is complete.
+    fn next(&mut self, searcher: &Searcher) -> Option<Match> {
+        while let Some(line) = self.read_line() {
+            let selected = searcher.regex.is_match(&line.line) != searcher.invert;
+            if selected {
+                let spans = if searcher.invert {
+                    Vec::new()
+                } else {
+                    searcher.regex.find_all(&line.line)
+                };
+                let found = Match {
+                    file: self.path.clone(),
+                    line_number: line.line_number,
+                    byte_offset: line.byte_offset,
+                    line: line.line,
+                    spans,
+                    before: self.before.drain(..).collect(),
+                    after: Vec::new(),
+                };
+                if searcher.first_only {
+                    self.offset = self.contents.len();
+                }
+                self.after = searcher.after_context;
+                // A match still waiting for context has all it will get.
+                let previous = self.pending.replace(found);
+                if previous.is_some() {
+                    return previous;
+                }
+                if self.after == 0 {
+                    return self.pending.take();
+                }
+            } else if let Some(pending) = &mut self.pending {
+                pending.after.push(line);
+                self.after -= 1;
+                if self.after == 0 {
+                    return self.pending.take();
+                }
+            } else if searcher.before_context > 0 {
+                if self.before.len() == searcher.before_context {
+                    self.before.pop_front();
+                }
+                self.before.push_back(line);
+            }
+        }
+        self.pending.take()
+    }
+
+    /// Read the next line, as context until it turns out to be selected.
+    fn read_line(&mut self) -> Option<ContextLine> {
+        let rest = self
+            .contents
+            .get(self.offset..)
+            .filter(|rest| !rest.is_empty())?;
+        let (line, length) = match rest.iter().position(|&byte| byte == b'\n') {
+            Some(end) => (&rest[..end], end + 1),
+            None => (rest, rest.len()),
+        };
+        let line = ContextLine {
+            line_number: self.line_number + 1,
+            byte_offset: self.offset,
+            line: line.to_vec(),
+        };
+        self.line_number += 1;
+        self.offset += length;
+        Some(line)
+    }
+}
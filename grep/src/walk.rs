@@ -0,0 +1,185 @@
+//! Finding the files to search under a directory, for `-r`.
+
+use crate::glob::Glob;
+use crate::PatternError;
+use anyhow::{Context, Error};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Which of the files found in a directory to search.
+#[derive(Debug, Default)]
+pub(crate) struct Filters {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl Filters {
+    pub(crate) fn new(include: &[String], exclude: &[String]) -> Result<Self, PatternError> {
+        let compile = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| Glob::new(glob))
+                .collect::<Result<_, _>>()
+        };
+        Ok(Filters {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// Whether to search the file with this name. Files must match an
+    /// `--include` glob, if there are any, and no `--exclude` glob.
+    fn allows(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.is_match(name)))
+            && !self.exclude.iter().any(|glob| glob.is_match(name))
+    }
+}
+
+/// Lists the files under `root` that pass `filters` and are not ignored by a
+/// `.gitignore` file, sorted by path. `.git` directories and symbolic links
+/// are skipped.
+pub(crate) fn files(root: &Path, filters: &Filters) -> Result<Vec<PathBuf>, Error> {
+    let mut walk = Walk {
+        filters,
+        ignores: Vec::new(),
+        files: Vec::new(),
+    };
+    walk.visit(root, "")?;
+    Ok(walk.files)
+}
+
+struct Walk<'f> {
+    filters: &'f Filters,
+    /// The `.gitignore` files of the directories being visited, outermost
+    /// first.
+    ignores: Vec<Gitignore>,
+    files: Vec<PathBuf>,
+}
+
+impl Walk<'_> {
+    /// Visit `dir`, whose path relative to the root is `relative`.
+    fn visit(&mut self, dir: &Path, relative: &str) -> Result<(), Error> {
+        let gitignore = dir.join(".gitignore");
+        let has_gitignore = gitignore.is_file();
+        if has_gitignore {
+            let text = fs::read_to_string(&gitignore)
+                .with_context(|| format!("cannot read '{}'", gitignore.display()))?;
+            self.ignores.push(Gitignore::parse(relative, &text));
+        }
+
+        let mut entries = fs::read_dir(dir)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .with_context(|| format!("cannot read directory '{}'", dir.display()))?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let path = if relative.is_empty() {
+                name.to_string()
+            } else {
+                format!("{relative}/{name}")
+            };
+            let file_type = entry
+                .file_type()
+                .with_context(|| format!("cannot read '{}'", entry.path().display()))?;
+            let is_dir = file_type.is_dir();
+            if (is_dir && name == ".git") || self.ignored(&path, is_dir) {
+                continue;
+            }
+            if is_dir {
+                self.visit(&entry.path(), &path)?;
+            } else if file_type.is_file() && self.filters.allows(&name) {
+                self.files.push(entry.path());
+            }
+        }
+
+        if has_gitignore {
+            self.ignores.pop();
+        }
+        Ok(())
+    }
+
+    /// Whether the `.gitignore` files ignore `path`. The innermost file with
+    /// a rule for the path decides.
+    fn ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.ignores
+            .iter()
+            .rev()
+            .find_map(|gitignore| gitignore.ignores(path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+/// The rules of one `.gitignore` file.
+struct Gitignore {
+    /// The directory the file is in, relative to the root, with a trailing
+    /// `/` unless it is the root.
+    dir: String,
+    rules: Vec<Rule>,
+}
+
+struct Rule {
+    glob: Glob,
+    /// A `!` rule, which unignores what it matches.
+    negated: bool,
+    /// A rule ending in `/`, which only matches directories.
+    dir_only: bool,
+    /// A rule with a `/` before its end, which matches paths relative to the
+    /// `.gitignore` file's directory rather than names at any depth.
+    anchored: bool,
+}
+
+impl Gitignore {
+    fn parse(dir: &str, text: &str) -> Self {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim_end();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let (negated, line) = match line.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (dir_only, line) = match line.strip_suffix('/') {
+                    Some(rest) => (true, rest),
+                    None => (false, line),
+                };
+                let anchored = line.contains('/');
+                let line = line.strip_prefix('/').unwrap_or(line);
+                // Git skips rules it cannot make sense of, and so do we.
+                let glob = Glob::new(line).ok()?;
+                Some(Rule {
+                    glob,
+                    negated,
+                    dir_only,
+                    anchored,
+                })
+            })
+            .collect();
+        Gitignore {
+            dir: if dir.is_empty() {
+                String::new()
+            } else {
+                format!("{dir}/")
+            },
+            rules,
+        }
+    }
+
+    /// Whether this file ignores `path`, or None if none of its rules match
+    /// it. The last rule that matches decides.
+    fn ignores(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let path = path.strip_prefix(&self.dir)?;
+        let name = path.rsplit('/').next().unwrap_or(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && rule.glob.is_match(if rule.anchored { path } else { name })
+            })
+            .map(|rule| !rule.negated)
+    }
+}
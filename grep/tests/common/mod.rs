@@ -0,0 +1,58 @@
+// Each test file uses only some of these helpers.
+#![allow(dead_code)]
+
+use grep::{grep, Flags};
+use std::fs;
+use std::path::PathBuf;
+
+/// Files with the given contents, named after the test so that tests running
+/// at the same time do not share files, removed when dropped.
+pub struct Files(pub Vec<PathBuf>);
+
+impl Files {
+    pub fn new(test: &str, files: &[&[u8]]) -> Self {
+        let paths = (0..files.len())
+            .map(|n| std::env::temp_dir().join(format!("{}{n}.txt", prefix(test))))
+            .collect();
+        let created = Files(paths);
+        for (path, contents) in created.0.iter().zip(files) {
+            fs::write(path, contents).unwrap();
+        }
+        created
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.0.iter().map(|path| path.to_str().unwrap()).collect()
+    }
+}
+
+impl Drop for Files {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// The start of the names of the files of a test.
+fn prefix(test: &str) -> String {
+    format!("grep-{}-{test}-", std::process::id())
+}
+
+/// Search files with the given contents, named `0.txt`, `1.txt` and so on in
+/// the output.
+pub fn search(
+    test: &str,
+    files: &[&[u8]],
+    pattern: &str,
+    flags: &[&str],
+) -> anyhow::Result<Vec<String>> {
+    let files = Files::new(test, files);
+    let lines = grep(pattern, &Flags::new(flags), &files.names())?;
+    let prefix = std::env::temp_dir().join(prefix(test));
+    let prefix = prefix.to_str().unwrap();
+    Ok(lines
+        .into_iter()
+        .map(|line| line.replace(prefix, ""))
+        .collect())
+}
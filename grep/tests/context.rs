@@ -0,0 +1,119 @@
+mod common;
+
+use common::search;
+
+static NUMBERS: &str = "one
+two
+three
+four
+five
+six
+seven
+eight
+nine
+ten
+";
+
+fn matches(test: &str, pattern: &str, flags: &[&str]) -> Vec<String> {
+    search(test, &[NUMBERS.as_bytes()], pattern, flags).unwrap()
+}
+
+#[test]
+fn after_context() {
+    assert_eq!(
+        matches("after", "three", &["-n", "-A", "2"]),
+        ["3:three", "4-four", "5-five"]
+    );
+    assert_eq!(
+        matches("after_attached", "three", &["-A2"]),
+        ["three", "four", "five"]
+    );
+}
+
+#[test]
+fn before_context() {
+    assert_eq!(
+        matches("before", "three", &["-n", "-B", "5"]),
+        ["1-one", "2-two", "3:three"]
+    );
+}
+
+#[test]
+fn context_on_both_sides() {
+    assert_eq!(
+        matches("both", "five", &["-n", "-C1"]),
+        ["4-four", "5:five", "6-six"]
+    );
+    assert_eq!(
+        matches("override", "five", &["-n", "-A", "0", "-C", "1"]),
+        ["4-four", "5:five"]
+    );
+}
+
+#[test]
+fn groups_are_separated() {
+    assert_eq!(
+        matches("separated", "two|nine", &["-E", "-n", "-A1"]),
+        ["2:two", "3-three", "--", "9:nine", "10-ten"]
+    );
+}
+
+#[test]
+fn overlapping_and_adjacent_windows_merge() {
+    assert_eq!(
+        matches("overlapping", "three|five", &["-E", "-n", "-C1"]),
+        ["2-two", "3:three", "4-four", "5:five", "6-six"]
+    );
+    assert_eq!(
+        matches("adjacent", "two|five", &["-E", "-n", "-A1", "-B1"]),
+        ["1-one", "2:two", "3-three", "4-four", "5:five", "6-six"]
+    );
+}
+
+#[test]
+fn selected_lines_are_not_repeated_as_context() {
+    assert_eq!(
+        matches("repeated", "^t", &["-E", "-n", "-C1"]),
+        ["1-one", "2:two", "3:three", "4-four", "--", "9-nine", "10:ten"]
+    );
+}
+
+#[test]
+fn context_of_inverted_matches() {
+    assert_eq!(
+        matches("invert", "e", &["-v", "-n", "-B1"]),
+        ["1-one", "2:two", "3-three", "4:four", "5-five", "6:six"]
+    );
+}
+
+#[test]
+fn file_names_use_the_same_marks() {
+    assert_eq!(
+        search(
+            "names",
+            &[b"cat\ndog\n", b"cow\ncat\n"],
+            "cat",
+            &["-n", "-A1"]
+        )
+        .unwrap(),
+        ["0.txt:1:cat", "0.txt-2-dog", "--", "1.txt:2:cat"]
+    );
+}
+
+#[test]
+fn files_only_ignores_context() {
+    assert_eq!(
+        search("files_only", &[b"cat\ndog\n"], "dog", &["-l", "-C1"]).unwrap(),
+        ["0.txt"]
+    );
+}
+
+#[test]
+fn invalid_context_lengths_are_errors() {
+    for flags in [&["-A"][..], &["-A", "x"], &["-B-1"], &["-C", ""]] {
+        assert!(
+            search("invalid", &[NUMBERS.as_bytes()], "one", flags).is_err(),
+            "{flags:?}"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::search;
+
+#[test]
+fn messages_for_each_file_and_match() {
+    assert_eq!(
+        search(
+            "messages",
+            &[b"cat and cat\ndog\n", b"cow\n", b"a cat\n"],
+            "cat",
+            &["--json"]
+        )
+        .unwrap(),
+        [
+            r#"{"type":"begin","data":{"path":{"text":"0.txt"}}}"#,
+            r#"{"type":"match","data":{"path":{"text":"0.txt"},"lines":{"text":"cat and cat"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"cat"},"start":0,"end":3},{"match":{"text":"cat"},"start":8,"end":11}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"0.txt"},"stats":{"searches_with_match":1,"matched_lines":1,"matches":2}}}"#,
+            r#"{"type":"begin","data":{"path":{"text":"2.txt"}}}"#,
+            r#"{"type":"match","data":{"path":{"text":"2.txt"},"lines":{"text":"a cat"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"cat"},"start":2,"end":5}]}}"#,
+            r#"{"type":"end","data":{"path":{"text":"2.txt"},"stats":{"searches_with_match":1,"matched_lines":1,"matches":1}}}"#,
+            r#"{"type":"summary","data":{"stats":{"searches_with_match":2,"matched_lines":2,"matches":3}}}"#,
+        ]
+    );
+}
+
+#[test]
+fn context_lines_have_their_own_messages() {
+    let output = search(
+        "context",
+        &[b"one\ntwo\nthree\n"],
+        "two",
+        &["--json", "-C1"],
+    )
+    .unwrap();
+    assert_eq!(
+        output[1..4],
+        [
+            r#"{"type":"context","data":{"path":{"text":"0.txt"},"lines":{"text":"one"},"line_number":1,"absolute_offset":0,"submatches":[]}}"#,
+            r#"{"type":"match","data":{"path":{"text":"0.txt"},"lines":{"text":"two"},"line_number":2,"absolute_offset":4,"submatches":[{"match":{"text":"two"},"start":0,"end":3}]}}"#,
+            r#"{"type":"context","data":{"path":{"text":"0.txt"},"lines":{"text":"three"},"line_number":3,"absolute_offset":8,"submatches":[]}}"#,
+        ]
+    );
+}
+
+#[test]
+fn special_characters_are_escaped() {
+    let output = search(
+        "escaped",
+        &["say \"hi\"\t\\ \u{1} ünï\r\n".as_bytes()],
+        "hi",
+        &["--json"],
+    )
+    .unwrap();
+    assert!(
+        output[1].contains(r#""lines":{"text":"say \"hi\"\t\\ \u0001 ünï\r"}"#),
+        "{}",
+        output[1]
+    );
+}
+
+#[test]
+fn bytes_that_are_not_utf8_are_base64() {
+    let output = search("bytes", &[b"caf\xe9 ok\n"], "caf. ", &["--json", "-E"]).unwrap();
+    assert!(
+        output[1].contains(r#""lines":{"bytes":"Y2Fm6SBvaw=="}"#),
+        "{}",
+        output[1]
+    );
+    assert!(
+        output[1].contains(r#""submatches":[{"match":{"bytes":"Y2Fm6SA="},"start":0,"end":5}]"#),
+        "{}",
+        output[1]
+    );
+}
+
+#[test]
+fn inverted_matches_have_no_submatches() {
+    let output = search("invert", &[b"cat\ndog\n"], "cat", &["--json", "-v"]).unwrap();
+    assert!(output[1].contains(r#""lines":{"text":"dog"}"#));
+    assert!(output[1].ends_with(r#""submatches":[]}}"#));
+}
+
+#[test]
+fn no_matches_is_just_a_summary() {
+    assert_eq!(
+        search("none", &[b"cat\n"], "dog", &["--json"]).unwrap(),
+        [
+            r#"{"type":"summary","data":{"stats":{"searches_with_match":0,"matched_lines":0,"matches":0}}}"#
+        ]
+    );
+}
+
+#[test]
+fn files_only_is_an_error() {
+    assert!(search("files_only", &[b"cat\n"], "cat", &["--json", "-l"]).is_err());
+}
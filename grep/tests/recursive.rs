@@ -0,0 +1,164 @@
+use grep::{grep, Flags};
+use std::fs;
+use std::path::PathBuf;
+
+/// A directory tree to search, removed when dropped.
+struct Tree {
+    root: PathBuf,
+}
+
+impl Tree {
+    /// Create a tree named after the test, so that tests running at the same
+    /// time do not share files.
+    fn new(test: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("grep-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let tree = Tree { root };
+        tree.write(".gitignore", b"*.log\nbuild/\n!keep.log\n/top.txt\n");
+        tree.write("a.txt", b"cat\n");
+        tree.write("b.rs", b"let cat = 1;\n");
+        tree.write("top.txt", b"cat at the top\n");
+        tree.write("debug.log", b"cat in a log\n");
+        tree.write("keep.log", b"cat in a kept log\n");
+        tree.write("image.bin", b"cat\0\x01\x02\n");
+        tree.write("build/out.txt", b"cat that was built\n");
+        tree.write(".git/config", b"cat in git\n");
+        tree.write("sub/.gitignore", b"*.rs\n");
+        tree.write("sub/c.txt", b"dog\ncat\n");
+        tree.write("sub/d.rs", b"cat in sub\n");
+        tree.write("sub/top.txt", b"cat below the top\n");
+        tree
+    }
+
+    fn write(&self, path: &str, contents: &[u8]) {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn path(&self, path: &str) -> String {
+        self.root.join(path).to_str().unwrap().to_string()
+    }
+
+    /// Search the tree, with the root taken off the paths in the output.
+    fn grep(&self, pattern: &str, flags: &[&str], files: &[&str]) -> anyhow::Result<Vec<String>> {
+        let files: Vec<String> = files.iter().map(|file| self.path(file)).collect();
+        let files: Vec<&str> = files.iter().map(String::as_str).collect();
+        let prefix = format!("{}/", self.root.display());
+        let lines = grep(pattern, &Flags::new(flags), &files)?;
+        Ok(lines
+            .into_iter()
+            .map(|line| line.replace(&prefix, ""))
+            .collect())
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[test]
+fn files_are_searched_in_path_order_skipping_ignored_and_binary_files() {
+    let tree = Tree::new("order");
+    assert_eq!(
+        tree.grep("cat", &["-r"], &[""]).unwrap(),
+        [
+            "a.txt:cat",
+            "b.rs:let cat = 1;",
+            "keep.log:cat in a kept log",
+            "sub/c.txt:cat",
+            "sub/top.txt:cat below the top",
+        ]
+    );
+}
+
+#[test]
+fn line_numbers_and_file_names_combine() {
+    let tree = Tree::new("numbers");
+    assert_eq!(
+        tree.grep("cat", &["-r", "-n", "--include=*.txt"], &["sub"])
+            .unwrap(),
+        ["sub/c.txt:2:cat", "sub/top.txt:1:cat below the top"]
+    );
+}
+
+#[test]
+fn include_takes_its_glob_either_way() {
+    let tree = Tree::new("include");
+    let expected = [
+        "a.txt:cat",
+        "sub/c.txt:cat",
+        "sub/top.txt:cat below the top",
+    ];
+    assert_eq!(
+        tree.grep("cat", &["-r", "--include=*.txt"], &[""]).unwrap(),
+        expected
+    );
+    assert_eq!(
+        tree.grep("cat", &["-r", "--include", "*.txt"], &[""])
+            .unwrap(),
+        expected
+    );
+    assert_eq!(
+        tree.grep(
+            "cat",
+            &["-r", "--include", "*.rs", "--include", "*.log"],
+            &[""]
+        )
+        .unwrap(),
+        ["b.rs:let cat = 1;", "keep.log:cat in a kept log"]
+    );
+}
+
+#[test]
+fn exclude_skips_matching_files() {
+    let tree = Tree::new("exclude");
+    assert_eq!(
+        tree.grep("cat", &["-r", "--exclude=*.txt", "--exclude=k?ep.*"], &[""])
+            .unwrap(),
+        ["b.rs:let cat = 1;"]
+    );
+}
+
+#[test]
+fn files_only_lists_each_file_once() {
+    let tree = Tree::new("files_only");
+    assert_eq!(
+        tree.grep("cat|dog", &["-r", "-l", "-E"], &["sub"]).unwrap(),
+        ["sub/c.txt", "sub/top.txt"]
+    );
+}
+
+#[test]
+fn files_named_directly_are_always_searched() {
+    let tree = Tree::new("named");
+    assert_eq!(
+        tree.grep(
+            "cat",
+            &["-r", "--exclude=*.log"],
+            &["debug.log", "image.bin"]
+        )
+        .unwrap(),
+        ["debug.log:cat in a log", "image.bin:cat\0\u{1}\u{2}"]
+    );
+}
+
+#[test]
+fn a_single_named_file_has_no_name_prefix() {
+    let tree = Tree::new("single");
+    assert_eq!(tree.grep("cat", &["-r"], &["a.txt"]).unwrap(), ["cat"]);
+}
+
+#[test]
+fn directories_need_the_recursive_flag() {
+    let tree = Tree::new("not_recursive");
+    assert!(grep("cat", &Flags::new(&[]), &[&tree.path("")]).is_err());
+}
+
+#[test]
+fn options_without_values_are_errors() {
+    let tree = Tree::new("missing_value");
+    assert!(tree.grep("cat", &["-r", "--include"], &[""]).is_err());
+}
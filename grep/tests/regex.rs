@@ -0,0 +1,160 @@
+mod common;
+
+use common::search;
+use grep::PatternError;
+
+static ANIMALS: &str = "cat
+cats and dogs
+dog
+The 3 little pigs
+concatenate
+aaa
+a{2}
+Кошка и собака
+";
+
+fn matches(test: &str, pattern: &str, flags: &[&str]) -> Vec<String> {
+    search(test, &[ANIMALS.as_bytes()], pattern, flags).unwrap()
+}
+
+#[test]
+fn patterns_are_literal_without_the_extended_flag() {
+    assert_eq!(matches("literal", "a{2}", &[]), ["a{2}"]);
+    assert_eq!(matches("literal_dot", "c.t", &[]), Vec::<String>::new());
+}
+
+#[test]
+fn alternation() {
+    assert_eq!(
+        matches("alternation", "pigs|dog", &["-E", "-n"]),
+        ["2:cats and dogs", "3:dog", "4:The 3 little pigs"]
+    );
+}
+
+#[test]
+fn anchors() {
+    assert_eq!(
+        matches("start", "^c", &["-E"]),
+        ["cat", "cats and dogs", "concatenate"]
+    );
+    assert_eq!(
+        matches("end", "s$", &["-E"]),
+        ["cats and dogs", "The 3 little pigs"]
+    );
+    assert_eq!(matches("both", "^(cat|dog)$", &["-E"]), ["cat", "dog"]);
+}
+
+#[test]
+fn character_classes() {
+    assert_eq!(
+        matches("range", "[0-9]", &["-E"]),
+        ["The 3 little pigs", "a{2}"]
+    );
+    assert_eq!(
+        matches("posix", "^[[:upper:]]", &["-E"]),
+        ["The 3 little pigs", "Кошка и собака"]
+    );
+    assert_eq!(
+        matches("negated", "^[^a-z]", &["-E"]),
+        ["The 3 little pigs", "Кошка и собака"]
+    );
+    assert_eq!(
+        matches("escape", "\\d", &["-E"]),
+        ["The 3 little pigs", "a{2}"]
+    );
+    assert_eq!(matches("bracket", "[]{}]", &["-E"]), ["a{2}"]);
+}
+
+#[test]
+fn repetition_and_groups() {
+    assert_eq!(matches("bound", "^a{3}$", &["-E"]), ["aaa"]);
+    assert_eq!(
+        matches("range_bound", "^a{1,2}$", &["-E"]),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        matches("group", "^(con|cat|en|ate)+$", &["-E"]),
+        ["cat", "concatenate"]
+    );
+    assert_eq!(matches("optional", "^cats?$", &["-E"]), ["cat"]);
+    assert_eq!(matches("literal_brace", "a{", &["-E"]), ["a{2}"]);
+}
+
+#[test]
+fn word_boundaries() {
+    assert_eq!(matches("boundary", "\\bcat\\b", &["-E"]), ["cat"]);
+    assert_eq!(matches("not_boundary", "\\Bcat", &["-E"]), ["concatenate"]);
+}
+
+#[test]
+fn dot_matches_one_character_however_many_bytes_it_takes() {
+    assert_eq!(matches("dot", "^.{5} и", &["-E"]), ["Кошка и собака"]);
+}
+
+#[test]
+fn other_flags_apply_on_top_of_extended_patterns() {
+    assert_eq!(
+        matches("ignore_case", "^(кошка|the)", &["-E", "-i"]),
+        ["The 3 little pigs", "Кошка и собака"]
+    );
+    assert_eq!(
+        matches("whole_line", "cat|dog", &["-E", "-x"]),
+        ["cat", "dog"]
+    );
+    assert_eq!(
+        matches("invert", "[aeiou]", &["-E", "-v", "-n"]),
+        ["8:Кошка и собака"]
+    );
+    assert_eq!(
+        matches("class_ignore_case", "[A-Z]+", &["-E", "-i", "-x"]),
+        ["cat", "dog", "concatenate", "aaa"]
+    );
+}
+
+#[test]
+fn bytes_that_are_not_utf8_match_dot() {
+    let contents = b"caf\xe9\ncafe\n";
+    assert_eq!(
+        search("not_utf8", &[contents], "^caf.$", &["-E", "-n"]).unwrap(),
+        ["1:caf\u{fffd}", "2:cafe"]
+    );
+}
+
+#[test]
+fn nested_repetition_does_not_blow_up() {
+    let mut contents = "a".repeat(20_000);
+    contents.push('\n');
+    for pattern in ["(a*)*b", "(a|aa)+$b", "(a?){50}a{50}c"] {
+        assert!(search("blow_up", &[contents.as_bytes()], pattern, &["-E"])
+            .unwrap()
+            .is_empty());
+    }
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    for (pattern, position) in [
+        ("(cat", Some(0)),
+        ("cat)", Some(3)),
+        ("a{3,2}", Some(1)),
+        ("[z-a]", Some(1)),
+        ("[abc", Some(0)),
+        ("[[:vowel:]]", Some(1)),
+        ("*a", Some(0)),
+        ("(a)\\1", Some(3)),
+        ("a\\", Some(1)),
+        ("a{1001}", Some(2)),
+        ("((a{1000}){1000}){1000}", None),
+    ] {
+        let error = search("invalid", &[ANIMALS.as_bytes()], pattern, &["-E"]).unwrap_err();
+        let error = error
+            .downcast_ref::<PatternError>()
+            .unwrap_or_else(|| panic!("expected a pattern error for {pattern:?}, got {error:?}"));
+        assert_eq!(error.position(), position, "{pattern:?}: {error}");
+    }
+}
+
+#[test]
+fn unknown_flags_are_errors() {
+    assert!(search("unknown_flag", &[ANIMALS.as_bytes()], "cat", &["-Q"]).is_err());
+}
@@ -0,0 +1,122 @@
+// Spans are lists of ranges, some of which have only one.
+#![allow(clippy::single_range_in_vec_init)]
+
+mod common;
+
+use common::Files;
+use grep::{search, ContextLine, Flags, Match};
+
+static POEM: &str = "The cat sat on the mat.
+Кошка спит.
+Nothing here
+cat and cat
+";
+
+/// Search the files made for a test.
+fn matches(files: &Files, pattern: &str, flags: &[&str]) -> Vec<Match> {
+    search(pattern, &Flags::new(flags), &files.names())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn context(line_number: usize, byte_offset: usize, line: &str) -> ContextLine {
+    ContextLine {
+        line_number,
+        byte_offset,
+        line: line.into(),
+    }
+}
+
+#[test]
+fn matches_carry_where_they_are_and_what_matched() {
+    let files = Files::new("fields", &[POEM.as_bytes()]);
+    let found = matches(&files, "cat", &[]);
+    assert_eq!(
+        found,
+        [
+            Match {
+                file: files.0[0].clone(),
+                line_number: 1,
+                byte_offset: 0,
+                line: "The cat sat on the mat.".into(),
+                spans: vec![4..7],
+                before: vec![],
+                after: vec![],
+            },
+            Match {
+                file: files.0[0].clone(),
+                line_number: 4,
+                byte_offset: 58,
+                line: "cat and cat".into(),
+                spans: vec![0..3, 8..11],
+                before: vec![],
+                after: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn spans_are_byte_ranges() {
+    let files = Files::new("bytes", &[POEM.as_bytes()]);
+    let found = matches(&files, "спит", &[]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].byte_offset, 24);
+    assert_eq!(found[0].spans, [11..19]);
+}
+
+#[test]
+fn spans_are_leftmost_longest_and_do_not_overlap() {
+    let files = Files::new("longest", &[POEM.as_bytes()]);
+    let found = matches(&files, "[a-z]*at", &["-E"]);
+    assert_eq!(found[0].spans, [4..7, 8..11, 19..22]);
+    let found = matches(&files, "x*", &["-E"]);
+    assert_eq!(found[3].spans.len(), "cat and cat".len() + 1);
+}
+
+#[test]
+fn context_belongs_to_the_nearest_match_before_it() {
+    let files = Files::new("context", &[b"a\nb\nmatch\nc\nd\nmatch\ne\n"]);
+    let found = matches(&files, "match", &["-C", "2"]);
+    assert_eq!(found[0].before, [context(1, 0, "a"), context(2, 2, "b")]);
+    assert_eq!(found[0].after, [context(4, 10, "c"), context(5, 12, "d")]);
+    assert_eq!(found[1].before, []);
+    assert_eq!(found[1].after, [context(7, 20, "e")]);
+}
+
+#[test]
+fn inverted_matches_have_no_spans() {
+    let files = Files::new("invert", &[POEM.as_bytes()]);
+    let found = matches(&files, "cat", &["-v"]);
+    let lines: Vec<_> = found.iter().map(|found| found.line_number).collect();
+    assert_eq!(lines, [2, 3]);
+    assert!(found.iter().all(|found| found.spans.is_empty()));
+}
+
+#[test]
+fn files_only_gives_the_first_match_of_each_file() {
+    let files = Files::new("files_only", &[POEM.as_bytes()]);
+    let found = matches(&files, "a", &["-l", "-B1"]);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].line_number, 1);
+    let found = matches(&files, "and", &["-l", "-B1"]);
+    assert_eq!(found[0].before, []);
+}
+
+#[test]
+fn unreadable_files_are_errors_and_the_search_goes_on() {
+    let files = Files::new("unreadable", &[POEM.as_bytes()]);
+    let missing = format!("{}.missing", files.names()[0]);
+    let results: Vec<_> = search("Nothing", &Flags::new(&[]), &[&missing, files.names()[0]])
+        .unwrap()
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_err());
+    assert_eq!(results[1].as_ref().unwrap().line_number, 3);
+}
+
+#[test]
+fn invalid_patterns_fail_before_searching() {
+    assert!(search("(", &Flags::new(&["-E"]), &["does-not-exist"]).is_err());
+}
@@ -0,0 +1,169 @@
+use crate::{CellError, CellId, ComputeCellId, ComputeStats, InputCellId, Reactor};
+use std::fmt::{self, Write};
+
+/// What [`Reactor::inspect`] knows about a cell.
+#[derive(Debug, PartialEq)]
+pub struct CellInfo<'r, T> {
+    pub id: CellId,
+    /// The cached value, or None for a compute cell that holds an error or
+    /// for a lazy compute cell that is stale.
+    pub value: Option<&'r T>,
+    /// The error held by a compute cell, if it is current and failed.
+    pub error: Option<&'r CellError>,
+    /// The cells a compute cell is computed from, in argument order.
+    pub dependencies: &'r [CellId],
+    pub dependents: &'r [ComputeCellId],
+    pub callbacks: usize,
+    pub subscribers: usize,
+    pub lazy: bool,
+    /// How often a compute cell has been recomputed; None for input cells.
+    pub stats: Option<ComputeStats>,
+}
+
+impl<'a, T: Clone + PartialEq> Reactor<'a, T> {
+    /// Lists every cell: input cells and then compute cells, each in
+    /// creation order.
+    pub fn cells(&self) -> impl Iterator<Item = CellInfo<'_, T>> {
+        let inputs = (0..self.inputs.len()).map(|i| CellId::Input(InputCellId(i)));
+        let computes = (0..self.computes.len()).map(|i| CellId::Compute(ComputeCellId(i)));
+        inputs.chain(computes).filter_map(|id| self.inspect(id))
+    }
+
+    /// Describes the specified cell without recomputing it, or returns None
+    /// if the cell does not exist.
+    pub fn inspect(&self, id: CellId) -> Option<CellInfo<'_, T>> {
+        let info = match id {
+            CellId::Input(input_id) => {
+                let input = self.input(input_id)?;
+                CellInfo {
+                    id,
+                    value: Some(&input.value),
+                    error: None,
+                    dependencies: &[],
+                    dependents: &input.dependents,
+                    callbacks: 0,
+                    subscribers: 0,
+                    lazy: false,
+                    stats: None,
+                }
+            }
+            CellId::Compute(compute_id) => {
+                let compute = self.compute(compute_id)?;
+                CellInfo {
+                    id,
+                    value: compute.value.get().and_then(|value| value.as_ref().ok()),
+                    error: compute.value.get().and_then(|value| value.as_ref().err()),
+                    dependencies: &compute.dependencies,
+                    dependents: &compute.dependents,
+                    callbacks: compute.callbacks.len(),
+                    subscribers: compute.subscribers.len(),
+                    lazy: compute.lazy,
+                    stats: Some(compute.stats.get()),
+                }
+            }
+        };
+        Some(info)
+    }
+
+    /// The cells the specified compute cell is computed from, in argument
+    /// order, or None if the cell does not exist.
+    pub fn dependencies(&self, id: ComputeCellId) -> Option<&[CellId]> {
+        self.compute(id).map(|compute| &compute.dependencies[..])
+    }
+
+    /// The compute cells that use the specified cell as an argument, in
+    /// creation order, or None if the cell does not exist.
+    pub fn dependents(&self, id: CellId) -> Option<&[ComputeCellId]> {
+        match id {
+            CellId::Input(id) => self.input(id).map(|input| &input.dependents[..]),
+            CellId::Compute(id) => self.compute(id).map(|compute| &compute.dependents[..]),
+        }
+    }
+
+    /// Renders the dependency graph in the Graphviz DOT language.
+    ///
+    /// Input cells are boxes and compute cells ellipses, dashed if they are
+    /// lazy. Each label shows the cell's value or error, and for compute cells how
+    /// often they have been evaluated and how many callbacks they have.
+    /// Edges point from a dependency to its dependent.
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// let input = r.create_input(1);
+    /// let _ = r.create_compute(&[CellId::Input(input)], |v| v[0] + 1);
+    /// assert_eq!(
+    ///     r.to_dot(),
+    ///     "digraph reactor {\n    \
+    ///          input0 [shape=box, label=\"input 0\\n1\"];\n    \
+    ///          compute0 [label=\"compute 0\\n2\\nevaluated 1, 0 callbacks\"];\n    \
+    ///          input0 -> compute0;\n\
+    ///      }\n",
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String
+    where
+        T: fmt::Debug,
+    {
+        let mut dot = String::from("digraph reactor {\n");
+        let mut edges = String::new();
+        for cell in self.cells() {
+            let value = match (cell.value, cell.error) {
+                (Some(value), _) => format!("{value:?}"),
+                (None, Some(error)) => format!("error: {error}"),
+                (None, None) => String::from("(stale)"),
+            };
+            let (label, attributes) = match (cell.id, cell.stats) {
+                (CellId::Input(InputCellId(i)), _) => {
+                    (format!("input {i}\n{value}"), "shape=box, ")
+                }
+                (CellId::Compute(ComputeCellId(i)), stats) => {
+                    let evaluations = stats.map_or(0, |stats| stats.evaluations);
+                    let label = format!(
+                        "compute {i}{}\n{value}\nevaluated {evaluations}, {} callbacks",
+                        if cell.lazy { " (lazy)" } else { "" },
+                        cell.callbacks,
+                    );
+                    (label, if cell.lazy { "style=dashed, " } else { "" })
+                }
+            };
+            writeln!(
+                dot,
+                "    {} [{attributes}label=\"{}\"];",
+                node(cell.id),
+                escape(&label)
+            )
+            .expect("writing to a String cannot fail");
+
+            for &dependency in cell.dependencies {
+                writeln!(edges, "    {} -> {};", node(dependency), node(cell.id))
+                    .expect("writing to a String cannot fail");
+            }
+        }
+        dot.push_str(&edges);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn node(id: CellId) -> String {
+    match id {
+        CellId::Input(InputCellId(i)) => format!("input{i}"),
+        CellId::Compute(ComputeCellId(i)) => format!("compute{i}"),
+    }
+}
+
+/// Escape text for a double-quoted DOT string.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
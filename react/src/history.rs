@@ -0,0 +1,145 @@
+use crate::{InputCellId, Reactor};
+use std::collections::{BTreeMap, VecDeque};
+use std::mem;
+
+/// The input values to put back to undo or redo one change.
+type Entry<T> = Vec<(InputCellId, T)>;
+
+/// The changes that [`Reactor::undo`] and [`Reactor::redo`] step through.
+pub(crate) struct History<T> {
+    /// Oldest first.
+    undo: VecDeque<Entry<T>>,
+    /// Most recently undone last.
+    redo: Vec<Entry<T>>,
+    limit: usize,
+}
+
+impl<T> History<T> {
+    pub(crate) fn new() -> Self {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: 0,
+        }
+    }
+
+    /// Remember an undoable change, forgetting the oldest one if there are
+    /// too many.
+    fn push_undo(&mut self, entry: Entry<T>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.undo.len() == self.limit {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+    }
+}
+
+impl<'a, T: Clone + PartialEq> Reactor<'a, T> {
+    /// Sets how many changes [`Reactor::undo`] can step back through.
+    ///
+    /// History is off, with a limit of 0, until this is called. Each
+    /// `set_value` call or batch that changes at least one input is one step;
+    /// once there are `limit` of them, each new change forgets the oldest.
+    /// Lowering the limit forgets the oldest changes right away.
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// r.set_history_limit(10);
+    /// let input = r.create_input(1);
+    /// let double = r
+    ///     .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+    ///     .unwrap();
+    ///
+    /// r.set_value(input, 2);
+    /// r.set_value(input, 3);
+    /// assert!(r.undo());
+    /// assert_eq!(r.value(CellId::Compute(double)), Some(4));
+    /// assert!(r.redo());
+    /// assert_eq!(r.value(CellId::Compute(double)), Some(6));
+    /// ```
+    pub fn set_history_limit(&mut self, limit: usize) {
+        let history = &mut self.history;
+        history.limit = limit;
+        let excess = history.undo.len().saturating_sub(limit);
+        history.undo.drain(..excess);
+        let excess = history.redo.len().saturating_sub(limit);
+        history.redo.drain(..excess);
+    }
+
+    /// Whether there is a change for [`Reactor::undo`] to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    /// Whether there is an undone change for [`Reactor::redo`] to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Puts the inputs changed by the last `set_value` call or batch back to
+    /// their earlier values, and propagates that as a change, calling
+    /// callbacks as usual.
+    ///
+    /// Returns false if there is nothing to undo. Inputs removed since the
+    /// change are skipped, and so are changes to no remaining inputs.
+    pub fn undo(&mut self) -> bool {
+        while let Some(entry) = self.history.undo.pop_back() {
+            let reverse = self.restore(entry);
+            if !reverse.is_empty() {
+                self.history.redo.push(reverse);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Makes the last undone change again, and propagates it like
+    /// [`Reactor::undo`] does.
+    ///
+    /// Returns false if there is nothing to redo. A new change to any input
+    /// forgets everything that could be redone.
+    pub fn redo(&mut self) -> bool {
+        while let Some(entry) = self.history.redo.pop() {
+            let reverse = self.restore(entry);
+            if !reverse.is_empty() {
+                self.history.push_undo(reverse);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Remember a change made by `set_value` or a batch, given the old values
+    /// of the inputs it set.
+    pub(crate) fn record(&mut self, changed: BTreeMap<InputCellId, T>) {
+        if self.history.limit == 0 {
+            return;
+        }
+        let entry: Entry<T> = changed
+            .into_iter()
+            .filter(|(id, old)| self.input(*id).is_some_and(|input| input.value != *old))
+            .collect();
+        if !entry.is_empty() {
+            self.history.push_undo(entry);
+            self.history.redo.clear();
+        }
+    }
+
+    /// Set the inputs in `entry` that still exist and propagate, returning
+    /// the values they had before.
+    fn restore(&mut self, entry: Entry<T>) -> Entry<T> {
+        let mut reverse = Vec::with_capacity(entry.len());
+        for (id, value) in entry {
+            if let Some(input) = self.input_mut(id) {
+                reverse.push((id, mem::replace(&mut input.value, value)));
+            }
+        }
+        let changed: Vec<InputCellId> = reverse.iter().map(|&(id, _)| id).collect();
+        self.propagate(&changed);
+        reverse
+    }
+}
@@ -1,6 +1,27 @@
+mod graph;
+mod history;
+mod shared;
+mod subscription;
+mod transaction;
+pub mod typed;
+
+pub use graph::CellInfo;
+pub use shared::SharedReactor;
+pub use subscription::{Change, Subscription};
+pub use transaction::Transaction;
+pub use typed::{AnyValue, ComputeCell, InputCell};
+
+use history::History;
+use std::cell::{Cell, OnceCell};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+
 /// `InputCellId` is a unique identifier for an input cell.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct InputCellId();
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InputCellId(usize);
 /// `ComputeCellId` is a unique identifier for a compute cell.
 /// Values of type `InputCellId` and `ComputeCellId` should not be mutually assignable,
 /// demonstrated by the following tests:
@@ -15,12 +36,12 @@ pub struct InputCellId();
 /// let input = r.create_input(111);
 /// let compute: react::InputCellId = r.create_compute(&[react::CellId::Input(input)], |_| 222).unwrap();
 /// ```
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct ComputeCellId();
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub struct CallbackId();
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ComputeCellId(usize);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CallbackId(usize);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CellId {
     Input(InputCellId),
     Compute(ComputeCellId),
@@ -32,99 +53,714 @@ pub enum RemoveCallbackError {
     NonexistentCallback,
 }
 
-pub struct Reactor<T> {
-    // Just so that the compiler doesn't complain about an unused type parameter.
-    // You probably want to delete this field.
-    dummy: ::std::marker::PhantomData<T>,
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemoveCellError {
+    NonexistentCell,
+    /// The cell cannot be removed while these compute cells depend on it.
+    HasDependents(Vec<ComputeCellId>),
+}
+
+/// Why a compute cell has no value: the compute function of a cell made
+/// with [`Reactor::create_try_compute`] failed, either for this cell or for
+/// one of the cells it depends on.
+#[derive(Clone, Debug)]
+pub struct CellError {
+    cell: ComputeCellId,
+    error: Arc<dyn Error + Send + Sync>,
+}
+
+impl CellError {
+    /// The cell whose compute function failed.
+    pub fn cell(&self) -> ComputeCellId {
+        self.cell
+    }
+
+    /// The error returned by the compute function.
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+}
+
+/// Errors are equal if they come from the same cell with the same message,
+/// so recomputing a cell that fails the same way again is not a change.
+impl PartialEq for CellError {
+    fn eq(&self, other: &Self) -> bool {
+        self.cell == other.cell
+            && (Arc::ptr_eq(&self.error, &other.error)
+                || self.error.to_string() == other.error.to_string())
+    }
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} failed: {}", self.cell, self.error)
+    }
+}
+
+impl Error for CellError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// How much work a compute cell has done, as reported by [`Reactor::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComputeStats {
+    /// The number of times the compute function has run.
+    pub evaluations: usize,
+    /// The number of changes to its dependencies that reached the cell.
+    pub invalidations: usize,
+}
+
+/// A graph of input cells and compute cells derived from them.
+///
+/// A compute cell can only depend on cells that already exist, so the order
+/// in which compute cells are created is a topological order of the graph.
+/// Changes are propagated in that order, which means every compute cell is
+/// recomputed at most once per change, and only after all of its
+/// dependencies have settled: no compute function or callback ever observes
+/// a mix of old and new values.
+///
+/// Compute cells made with [`Reactor::create_try_compute`] may fail, in
+/// which case they hold a [`CellError`] instead of a value, as do all the
+/// cells that depend on them.
+///
+/// Lazy compute cells, made with [`Reactor::create_lazy_compute`], are only
+/// marked stale by a change and recompute when their value is next needed.
+///
+/// Values only need to be `Clone`. Compute functions made with
+/// [`Reactor::create_compute_by_ref`] borrow their arguments instead of
+/// receiving copies, which matters for values such as strings and vectors.
+///
+/// A `Reactor<AnyValue>` can hold cells of different types, through the
+/// typed handles described in [`typed`].
+///
+/// Changes to inputs can be undone with [`Reactor::undo`], once
+/// [`Reactor::set_history_limit`] has turned history on.
+///
+/// The graph can be inspected with [`Reactor::cells`] and rendered with
+/// [`Reactor::to_dot`].
+///
+/// A `Reactor` belongs to one thread; [`SharedReactor`] wraps one for use
+/// from several.
+///
+/// Cell IDs are never reused, so the ID of a removed cell stays invalid.
+pub struct Reactor<'a, T> {
+    /// Input cells by ID, `None` once removed.
+    inputs: Vec<Option<Input<T>>>,
+    /// Compute cells by ID, `None` once removed. IDs are handed out in
+    /// creation order, which is also topological order.
+    computes: Vec<Option<Compute<'a, T>>>,
+    next_callback: usize,
+    /// The sequence number of the last update that changed a compute cell.
+    sequence: u64,
+    history: History<T>,
+}
+
+struct Input<T> {
+    value: T,
+    dependents: Vec<ComputeCellId>,
+}
+
+struct Compute<'a, T> {
+    /// The cached value or error, empty while a lazy cell is stale.
+    value: OnceCell<Result<T, CellError>>,
+    dependencies: Vec<CellId>,
+    function: ComputeFn<'a, T>,
+    dependents: Vec<ComputeCellId>,
+    callbacks: BTreeMap<CallbackId, Callback<'a, T>>,
+    subscribers: Vec<Sender<Change<T>>>,
+    /// The last value the subscribers know of, kept while the cell holds an
+    /// error so that its recovery can be sent as a change from it.
+    delivered: Option<T>,
+    lazy: bool,
+    stats: Cell<ComputeStats>,
+}
+
+impl<T> Compute<'_, T> {
+    /// Whether a change only marks the cell stale rather than recomputing
+    /// it. A lazy cell that is being watched is kept up to date so that its
+    /// callbacks fire and its subscribers hear about changes.
+    fn is_deferred(&self) -> bool {
+        self.lazy && self.callbacks.is_empty() && self.subscribers.is_empty()
+    }
+
+    fn count(&self, update: impl FnOnce(&mut ComputeStats)) {
+        let mut stats = self.stats.get();
+        update(&mut stats);
+        self.stats.set(stats);
+    }
+}
+
+type BoxError = Box<dyn Error + Send + Sync>;
+type ComputeFn<'a, T> = Box<dyn Fn(&[&T]) -> Result<T, BoxError> + 'a>;
+type Callback<'a, T> = Box<dyn FnMut(Result<T, CellError>) + 'a>;
+
+impl<'a, T: Clone + PartialEq> Default for Reactor<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-// You are guaranteed that Reactor will only be tested against types that are Copy + PartialEq.
-impl<T: Copy + PartialEq> Reactor<T> {
+impl<'a, T: Clone + PartialEq> Reactor<'a, T> {
     pub fn new() -> Self {
-        todo!()
-    }
-
-    // Creates an input cell with the specified initial value, returning its ID.
-    pub fn create_input(&mut self, _initial: T) -> InputCellId {
-        todo!()
-    }
-
-    // Creates a compute cell with the specified dependencies and compute function.
-    // The compute function is expected to take in its arguments in the same order as specified in
-    // `dependencies`.
-    // You do not need to reject compute functions that expect more arguments than there are
-    // dependencies (how would you check for this, anyway?).
-    //
-    // If any dependency doesn't exist, returns an Err with that nonexistent dependency.
-    // (If multiple dependencies do not exist, exactly which one is returned is not defined and
-    // will not be tested)
-    //
-    // Notice that there is no way to *remove* a cell.
-    // This means that you may assume, without checking, that if the dependencies exist at creation
-    // time they will continue to exist as long as the Reactor exists.
-    pub fn create_compute<F: Fn(&[T]) -> T>(
+        Reactor {
+            inputs: Vec::new(),
+            computes: Vec::new(),
+            next_callback: 0,
+            sequence: 0,
+            history: History::new(),
+        }
+    }
+
+    /// Creates an input cell with the specified initial value, returning its ID.
+    pub fn create_input(&mut self, initial: T) -> InputCellId {
+        self.inputs.push(Some(Input {
+            value: initial,
+            dependents: Vec::new(),
+        }));
+        InputCellId(self.inputs.len() - 1)
+    }
+
+    /// Creates a compute cell with the specified dependencies and compute function.
+    ///
+    /// The compute function takes its arguments in the same order as specified in
+    /// `dependencies`.
+    ///
+    /// If any dependency doesn't exist, returns an Err with that nonexistent dependency.
+    ///
+    /// The dependencies cannot be removed for as long as the new cell exists.
+    ///
+    /// The compute function receives a copy of each argument; see
+    /// [`Reactor::create_compute_by_ref`] for values that are costly to clone.
+    pub fn create_compute<F: Fn(&[T]) -> T + 'a>(
+        &mut self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId> {
+        self.insert_compute(dependencies, by_value(compute_func), false)
+    }
+
+    /// Like [`Reactor::create_compute`], but the compute function borrows its
+    /// arguments rather than receiving copies of them.
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// let first = r.create_input(String::from("Ada"));
+    /// let last = r.create_input(String::from("Lovelace"));
+    /// let full = r
+    ///     .create_compute_by_ref(&[CellId::Input(first), CellId::Input(last)], |v| {
+    ///         format!("{} {}", v[0], v[1])
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(r.value_ref(CellId::Compute(full)).unwrap(), "Ada Lovelace");
+    /// ```
+    pub fn create_compute_by_ref<F: Fn(&[&T]) -> T + 'a>(
+        &mut self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId> {
+        self.insert_compute(dependencies, by_ref(compute_func), false)
+    }
+
+    /// Creates a compute cell that only runs its compute function when its
+    /// value is needed.
+    ///
+    /// Instead of recomputing on every change, the cell is marked stale, and
+    /// recomputes when [`Reactor::value`] is called on it or on a cell that
+    /// depends on it. Attaching a callback brings it up to date and keeps it
+    /// that way while the callback is attached, since a callback can only
+    /// fire for a change that has been computed.
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// let input = r.create_input(1);
+    /// let square = r
+    ///     .create_lazy_compute(&[CellId::Input(input)], |v| v[0] * v[0])
+    ///     .unwrap();
+    /// for n in 2..10 {
+    ///     r.set_value(input, n);
+    /// }
+    /// assert_eq!(r.value(CellId::Compute(square)), Some(81));
+    /// assert_eq!(r.stats(square).unwrap().evaluations, 1);
+    /// ```
+    pub fn create_lazy_compute<F: Fn(&[T]) -> T + 'a>(
+        &mut self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId> {
+        self.insert_compute(dependencies, by_value(compute_func), true)
+    }
+
+    /// Like [`Reactor::create_lazy_compute`], but the compute function
+    /// borrows its arguments rather than receiving copies of them.
+    pub fn create_lazy_compute_by_ref<F: Fn(&[&T]) -> T + 'a>(
         &mut self,
-        _dependencies: &[CellId],
-        _compute_func: F,
+        dependencies: &[CellId],
+        compute_func: F,
     ) -> Result<ComputeCellId, CellId> {
-        todo!()
+        self.insert_compute(dependencies, by_ref(compute_func), true)
     }
 
-    // Retrieves the current value of the cell, or None if the cell does not exist.
-    //
-    // You may wonder whether it is possible to implement `get(&self, id: CellId) -> Option<&Cell>`
-    // and have a `value(&self)` method on `Cell`.
-    //
-    // It turns out this introduces a significant amount of extra complexity to this exercise.
-    // We chose not to cover this here, since this exercise is probably enough work as-is.
+    /// Creates a compute cell whose compute function may fail.
+    ///
+    /// While the function returns an error, the cell holds a [`CellError`]
+    /// instead of a value, and so does every cell that depends on it, without
+    /// running its own compute function. Use [`Reactor::try_value`] to tell
+    /// the error apart from a missing cell, and [`Reactor::add_try_callback`]
+    /// to be told when a cell fails or recovers.
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// let a = r.create_input(6i32);
+    /// let b = r.create_input(3);
+    /// let quotient = r
+    ///     .create_try_compute(&[CellId::Input(a), CellId::Input(b)], |v| {
+    ///         v[0].checked_div(v[1]).ok_or("division by zero")
+    ///     })
+    ///     .unwrap();
+    /// let plus_one = r
+    ///     .create_compute(&[CellId::Compute(quotient)], |v| v[0] + 1)
+    ///     .unwrap();
+    ///
+    /// r.set_value(b, 0);
+    /// let error = r.try_value(CellId::Compute(plus_one)).unwrap().unwrap_err();
+    /// assert_eq!(error.cell(), quotient);
+    /// assert_eq!(error.error().to_string(), "division by zero");
+    ///
+    /// r.set_value(b, 2);
+    /// assert_eq!(r.value(CellId::Compute(plus_one)), Some(4));
+    /// ```
+    pub fn create_try_compute<E, F>(
+        &mut self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+        F: Fn(&[T]) -> Result<T, E> + 'a,
+    {
+        let function = move |args: &[&T]| {
+            let args: Vec<T> = args.iter().map(|&arg| arg.clone()).collect();
+            compute_func(&args).map_err(Into::into)
+        };
+        self.insert_compute(dependencies, Box::new(function), false)
+    }
+
+    /// Reports how often the specified compute cell has been recomputed, or
+    /// None if the cell does not exist.
+    pub fn stats(&self, id: ComputeCellId) -> Option<ComputeStats> {
+        self.compute(id).map(|compute| compute.stats.get())
+    }
+
+    /// Retrieves the current value of the cell, or None if the cell does not
+    /// exist or holds an error.
     pub fn value(&self, id: CellId) -> Option<T> {
-        todo!("Get the value of the cell whose id is {id:?}")
-    }
-
-    // Sets the value of the specified input cell.
-    //
-    // Returns false if the cell does not exist.
-    //
-    // Similarly, you may wonder about `get_mut(&mut self, id: CellId) -> Option<&mut Cell>`, with
-    // a `set_value(&mut self, new_value: T)` method on `Cell`.
-    //
-    // As before, that turned out to add too much extra complexity.
-    pub fn set_value(&mut self, _id: InputCellId, _new_value: T) -> bool {
-        todo!()
-    }
-
-    // Adds a callback to the specified compute cell.
-    //
-    // Returns the ID of the just-added callback, or None if the cell doesn't exist.
-    //
-    // Callbacks on input cells will not be tested.
-    //
-    // The semantics of callbacks (as will be tested):
-    // For a single set_value call, each compute cell's callbacks should each be called:
-    // * Zero times if the compute cell's value did not change as a result of the set_value call.
-    // * Exactly once if the compute cell's value changed as a result of the set_value call.
-    //   The value passed to the callback should be the final value of the compute cell after the
-    //   set_value call.
-    pub fn add_callback<F: FnMut(T)>(
+        self.value_ref(id).cloned()
+    }
+
+    /// Borrows the current value of the cell, or returns None if the cell
+    /// does not exist or holds an error.
+    pub fn value_ref(&self, id: CellId) -> Option<&T> {
+        self.outcome(id)?.ok()
+    }
+
+    /// Retrieves the current value or error of the cell, or None if the cell
+    /// does not exist.
+    pub fn try_value(&self, id: CellId) -> Option<Result<T, CellError>> {
+        Some(self.outcome(id)?.cloned().map_err(Clone::clone))
+    }
+
+    /// Removes the specified input cell.
+    ///
+    /// Returns an Err listing the compute cells that depend on it, if there are any.
+    pub fn remove_input(&mut self, id: InputCellId) -> Result<(), RemoveCellError> {
+        self.remove(CellId::Input(id))
+    }
+
+    /// Removes the specified compute cell, along with its callbacks.
+    ///
+    /// Returns an Err listing the compute cells that depend on it, if there are any.
+    pub fn remove_compute(&mut self, id: ComputeCellId) -> Result<(), RemoveCellError> {
+        self.remove(CellId::Compute(id))
+    }
+
+    /// Removes the specified cell and every compute cell that depends on it,
+    /// directly or indirectly.
+    ///
+    /// Returns the compute cells removed along with it, in creation order.
+    pub fn remove_with_dependents(
+        &mut self,
+        id: CellId,
+    ) -> Result<Vec<ComputeCellId>, RemoveCellError> {
+        let mut removed = BTreeSet::new();
+        let mut stack = self
+            .dependents(id)
+            .ok_or(RemoveCellError::NonexistentCell)?
+            .to_vec();
+        while let Some(dependent) = stack.pop() {
+            if removed.insert(dependent) {
+                stack.extend(
+                    self.dependents(CellId::Compute(dependent))
+                        .unwrap_or_default(),
+                );
+            }
+        }
+
+        // Dependents always come after their dependencies, so removing in
+        // reverse creation order never leaves a cell with a dangling dependent.
+        for &dependent in removed.iter().rev() {
+            self.remove(CellId::Compute(dependent))?;
+        }
+        self.remove(id)?;
+        Ok(removed.into_iter().collect())
+    }
+
+    /// Sets the value of the specified input cell.
+    ///
+    /// Returns false if the cell does not exist.
+    pub fn set_value(&mut self, id: InputCellId, new_value: T) -> bool {
+        self.batch(|tx| tx.set(id, new_value))
+    }
+
+    /// Sets several input cells at once, as a single change.
+    ///
+    /// `f` sets inputs through the [`Transaction`] it is given. Once it
+    /// returns, the change is propagated as if all the inputs had been set at
+    /// the same moment, so each compute cell is recomputed at most once and its
+    /// callbacks fire at most once, with its final value:
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let seen = std::cell::RefCell::new(Vec::new());
+    /// let mut r = Reactor::new();
+    /// let a = r.create_input(1);
+    /// let b = r.create_input(2);
+    /// let sum = r
+    ///     .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+    ///     .unwrap();
+    ///
+    /// r.add_callback(sum, |v| seen.borrow_mut().push(v));
+    /// r.batch(|tx| {
+    ///     tx.set(a, 10);
+    ///     tx.set(b, 20);
+    /// });
+    /// assert_eq!(*seen.borrow(), [30]);
+    /// ```
+    pub fn batch<R>(&mut self, f: impl FnOnce(&mut Transaction<'_, 'a, T>) -> R) -> R {
+        let mut tx = Transaction::new(self);
+        let result = f(&mut tx);
+        let changed = tx.into_changed();
+        let ids: Vec<InputCellId> = changed.keys().copied().collect();
+        self.propagate(&ids);
+        self.record(changed);
+        result
+    }
+
+    /// Adds a callback to the specified compute cell.
+    ///
+    /// Returns the ID of the just-added callback, or None if the cell doesn't exist.
+    ///
+    /// For a single set_value call or batch, each compute cell's callbacks are called:
+    /// * Zero times if the compute cell's value did not change as a result.
+    /// * Exactly once if the compute cell's value changed as a result.
+    ///   The value passed to the callback is the final value of the compute cell.
+    ///
+    /// The callback is not called when the cell fails, and is called with the
+    /// new value when it recovers.
+    pub fn add_callback<F: FnMut(T) + 'a>(
+        &mut self,
+        id: ComputeCellId,
+        mut callback: F,
+    ) -> Option<CallbackId> {
+        self.add_try_callback(id, move |outcome| {
+            if let Ok(value) = outcome {
+                callback(value);
+            }
+        })
+    }
+
+    /// Like [`Reactor::add_callback`], but the callback is also called when
+    /// the cell fails, with its [`CellError`], or fails differently.
+    pub fn add_try_callback<F: FnMut(Result<T, CellError>) + 'a>(
         &mut self,
-        _id: ComputeCellId,
-        _callback: F,
+        id: ComputeCellId,
+        callback: F,
     ) -> Option<CallbackId> {
-        todo!()
+        self.pull(id)?;
+        let callback_id = CallbackId(self.next_callback);
+        self.compute_mut(id)?
+            .callbacks
+            .insert(callback_id, Box::new(callback));
+        self.next_callback += 1;
+        Some(callback_id)
+    }
+
+    /// Follows the changes to the specified compute cell through a channel.
+    ///
+    /// Returns None if the cell doesn't exist. The subscription receives a
+    /// [`Change`] whenever the cell's value changes to another value. Nothing
+    /// is sent when the cell fails, and its recovery is sent as a change from
+    /// the last value before the error, unless it recovers to that same
+    /// value. It can be read from another thread while the reactor keeps
+    /// being used:
+    ///
+    /// ```
+    /// use react::{CellId, Reactor};
+    ///
+    /// let mut r = Reactor::new();
+    /// let input = r.create_input(1);
+    /// let double = r
+    ///     .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+    ///     .unwrap();
+    /// let changes = r.subscribe(double).unwrap();
+    /// let reader = std::thread::spawn(move || {
+    ///     changes.map(|c| (c.old, c.new)).collect::<Vec<_>>()
+    /// });
+    ///
+    /// r.set_value(input, 2);
+    /// r.set_value(input, 3);
+    /// drop(r);
+    /// assert_eq!(reader.join().unwrap(), [(2, 4), (4, 6)]);
+    /// ```
+    pub fn subscribe(&mut self, id: ComputeCellId) -> Option<Subscription<T>> {
+        self.pull(id)?;
+        let (sender, receiver) = mpsc::channel();
+        let compute = self.compute_mut(id)?;
+        if compute.subscribers.is_empty() {
+            let value = compute.value.get().expect("the cell was just pulled");
+            compute.delivered = value.as_ref().ok().cloned();
+        }
+        compute.subscribers.push(sender);
+        Some(Subscription::new(receiver))
     }
 
-    // Removes the specified callback, using an ID returned from add_callback.
-    //
-    // Returns an Err if either the cell or callback does not exist.
-    //
-    // A removed callback should no longer be called.
+    /// Removes the specified callback, using an ID returned from add_callback.
+    ///
+    /// Returns an Err if either the cell or callback does not exist.
+    ///
+    /// A removed callback is no longer called.
     pub fn remove_callback(
         &mut self,
         cell: ComputeCellId,
         callback: CallbackId,
     ) -> Result<(), RemoveCallbackError> {
-        todo!(
-            "Remove the callback identified by the CallbackId {callback:?} from the cell {cell:?}"
-        )
+        self.compute_mut(cell)
+            .ok_or(RemoveCallbackError::NonexistentCell)?
+            .callbacks
+            .remove(&callback)
+            .map(drop)
+            .ok_or(RemoveCallbackError::NonexistentCallback)
     }
+
+    fn insert_compute(
+        &mut self,
+        dependencies: &[CellId],
+        function: ComputeFn<'a, T>,
+        lazy: bool,
+    ) -> Result<ComputeCellId, CellId> {
+        if let Some(&missing) = dependencies.iter().find(|&&id| !self.contains(id)) {
+            return Err(missing);
+        }
+
+        let id = ComputeCellId(self.computes.len());
+        for &dependency in dependencies {
+            let dependents = self.dependents_mut(dependency);
+            // A cell listed twice as a dependency is still one dependent.
+            if dependents.last() != Some(&id) {
+                dependents.push(id);
+            }
+        }
+        self.computes.push(Some(Compute {
+            value: OnceCell::new(),
+            dependencies: dependencies.to_vec(),
+            function,
+            dependents: Vec::new(),
+            callbacks: BTreeMap::new(),
+            subscribers: Vec::new(),
+            delivered: None,
+            lazy,
+            stats: Cell::default(),
+        }));
+        if !lazy {
+            self.pull(id);
+        }
+        Ok(id)
+    }
+
+    /// The value or error of the cell, first recomputing it if it is a stale
+    /// compute cell.
+    fn outcome(&self, id: CellId) -> Option<Result<&T, &CellError>> {
+        match id {
+            CellId::Input(id) => self.input(id).map(|input| Ok(&input.value)),
+            CellId::Compute(id) => self.pull(id).map(Result::as_ref),
+        }
+    }
+
+    /// Return the value or error of a compute cell, first recomputing it if it
+    /// is stale.
+    fn pull(&self, id: ComputeCellId) -> Option<&Result<T, CellError>> {
+        let compute = self.compute(id)?;
+        Some(compute.value.get_or_init(|| self.evaluate(id, compute)))
+    }
+
+    /// Run the compute function of `compute` on the current values of its
+    /// dependencies, or pass on the first error among them.
+    fn evaluate(&self, id: ComputeCellId, compute: &Compute<'a, T>) -> Result<T, CellError> {
+        let mut args = Vec::with_capacity(compute.dependencies.len());
+        for &dependency in &compute.dependencies {
+            let outcome = self
+                .outcome(dependency)
+                .expect("dependencies outlive their dependents");
+            args.push(outcome.map_err(Clone::clone)?);
+        }
+        compute.count(|stats| stats.evaluations += 1);
+        (compute.function)(&args).map_err(|error| CellError {
+            cell: id,
+            error: error.into(),
+        })
+    }
+
+    fn contains(&self, id: CellId) -> bool {
+        match id {
+            CellId::Input(id) => self.input(id).is_some(),
+            CellId::Compute(id) => self.compute(id).is_some(),
+        }
+    }
+
+    fn input(&self, id: InputCellId) -> Option<&Input<T>> {
+        self.inputs.get(id.0)?.as_ref()
+    }
+
+    fn input_mut(&mut self, id: InputCellId) -> Option<&mut Input<T>> {
+        self.inputs.get_mut(id.0)?.as_mut()
+    }
+
+    fn compute(&self, id: ComputeCellId) -> Option<&Compute<'a, T>> {
+        self.computes.get(id.0)?.as_ref()
+    }
+
+    fn compute_mut(&mut self, id: ComputeCellId) -> Option<&mut Compute<'a, T>> {
+        self.computes.get_mut(id.0)?.as_mut()
+    }
+
+    fn dependents_mut(&mut self, id: CellId) -> &mut Vec<ComputeCellId> {
+        let dependents = match id {
+            CellId::Input(id) => self.input_mut(id).map(|input| &mut input.dependents),
+            CellId::Compute(id) => self.compute_mut(id).map(|compute| &mut compute.dependents),
+        };
+        dependents.expect("dependencies outlive their dependents")
+    }
+
+    /// Remove a cell that no other cell depends on, unlinking it from its
+    /// own dependencies.
+    fn remove(&mut self, id: CellId) -> Result<(), RemoveCellError> {
+        let dependents = self
+            .dependents(id)
+            .ok_or(RemoveCellError::NonexistentCell)?;
+        if !dependents.is_empty() {
+            return Err(RemoveCellError::HasDependents(dependents.to_vec()));
+        }
+
+        match id {
+            CellId::Input(id) => self.inputs[id.0] = None,
+            CellId::Compute(id) => {
+                let compute = self.computes[id.0].take().expect("checked above");
+                for dependency in compute.dependencies {
+                    self.dependents_mut(dependency).retain(|&d| d != id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Recompute everything downstream of the `changed` inputs, then fire the
+    /// callbacks of every compute cell whose value changed.
+    fn propagate(&mut self, changed: &[InputCellId]) {
+        let mut pending: BTreeSet<ComputeCellId> = changed
+            .iter()
+            .filter_map(|&id| self.input(id))
+            .flat_map(|input| input.dependents.iter().copied())
+            .collect();
+
+        let mut updated = Vec::new();
+        while let Some(id) = pending.pop_first() {
+            let compute = self
+                .compute(id)
+                .expect("dependents outlive their dependencies");
+            compute.count(|stats| stats.invalidations += 1);
+            let value = (!compute.is_deferred()).then(|| self.evaluate(id, compute));
+
+            let compute = self.compute_mut(id).expect("checked above");
+            let old = compute.value.take();
+            let Some(value) = value else {
+                pending.extend(compute.dependents.iter().copied());
+                continue;
+            };
+            let old = old.expect("cells that are not deferred are current");
+            if value != old {
+                pending.extend(compute.dependents.iter().copied());
+                updated.push((id, old));
+            }
+            compute.value = OnceCell::from(value);
+        }
+
+        if !updated.is_empty() {
+            self.sequence += 1;
+        }
+        let sequence = self.sequence;
+        for (id, old) in updated {
+            let compute = self.compute_mut(id).expect("updated cells exist");
+            let new = compute.value.get().expect("updated cells are current");
+            for callback in compute.callbacks.values_mut() {
+                callback(new.clone());
+            }
+            if compute.subscribers.is_empty() {
+                continue;
+            }
+            let Ok(new) = new else {
+                continue;
+            };
+            // A cell recovering from an error changes from the last value
+            // its subscribers know of, which it may have recovered to.
+            let old = match old {
+                Ok(old) => Some(old),
+                Err(_) => compute.delivered.take().filter(|old| old != new),
+            };
+            compute.delivered = Some(new.clone());
+            let Some(old) = old else {
+                continue;
+            };
+            // Forget subscribers whose subscription has been dropped.
+            compute.subscribers.retain(|subscriber| {
+                let change = Change {
+                    cell: id,
+                    old: old.clone(),
+                    new: new.clone(),
+                    sequence,
+                };
+                subscriber.send(change).is_ok()
+            });
+        }
+    }
+}
+
+/// Adapt a compute function that takes its arguments by value.
+fn by_value<'a, T: Clone>(f: impl Fn(&[T]) -> T + 'a) -> ComputeFn<'a, T> {
+    Box::new(move |args: &[&T]| {
+        let args: Vec<T> = args.iter().map(|&arg| arg.clone()).collect();
+        Ok(f(&args))
+    })
+}
+
+/// Adapt a compute function that cannot fail.
+fn by_ref<'a, T>(f: impl Fn(&[&T]) -> T + 'a) -> ComputeFn<'a, T> {
+    Box::new(move |args: &[&T]| Ok(f(args)))
 }
@@ -0,0 +1,272 @@
+use crate::{
+    CallbackId, CellError, CellId, ComputeCellId, ComputeStats, InputCellId, Reactor,
+    RemoveCallbackError, RemoveCellError, Subscription, Transaction,
+};
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard};
+
+/// A [`Reactor`] that can be used from several threads at once.
+///
+/// Every method locks the reactor for its whole duration, so changes are
+/// propagated one at a time, in the order their `set_value` calls or batches
+/// took the lock, and each fires its callbacks exactly as it would on a
+/// [`Reactor`]. Callbacks run on the thread that made the change, while the
+/// reactor is locked; a callback that uses the same `SharedReactor` again
+/// deadlocks. Use [`SharedReactor::subscribe`] to hear about changes without
+/// holding up other threads.
+///
+/// Compute functions and callbacks must be `Send`, since any thread may end
+/// up running them:
+///
+/// ```compile_fail
+/// let r = react::SharedReactor::new();
+/// let input = r.create_input(1);
+/// let counter = std::rc::Rc::new(std::cell::Cell::new(0));
+/// let _ = r.create_compute(&[react::CellId::Input(input)], move |v| {
+///     counter.set(counter.get() + 1);
+///     v[0]
+/// });
+/// ```
+///
+/// ```
+/// use react::{CellId, SharedReactor};
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let r = Arc::new(SharedReactor::new());
+/// let input = r.create_input(0);
+/// let double = r
+///     .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+///     .unwrap();
+///
+/// let writers: Vec<_> = (1..=4)
+///     .map(|n| {
+///         let r = Arc::clone(&r);
+///         thread::spawn(move || r.batch(|tx| tx.set(input, tx.get(input).unwrap() + n)))
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+/// assert_eq!(r.value(CellId::Compute(double)), Some(20));
+/// ```
+pub struct SharedReactor<T> {
+    reactor: Mutex<SendReactor<T>>,
+}
+
+struct SendReactor<T>(Reactor<'static, T>);
+
+// SAFETY: The only parts of a `Reactor` that are not `Send` are its boxed
+// compute functions and callbacks, because the boxes forget that the
+// closures in them are `Send`. A `SendReactor` is only ever built empty by
+// `SharedReactor::new`, and every `SharedReactor` method that stores a
+// closure requires it to be `Send`, so every closure in the reactor is.
+unsafe impl<T: Send> Send for SendReactor<T> {}
+
+impl<T: Clone + PartialEq + Send> Default for SharedReactor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + PartialEq + Send> SharedReactor<T> {
+    pub fn new() -> Self {
+        SharedReactor {
+            reactor: Mutex::new(SendReactor(Reactor::new())),
+        }
+    }
+
+    /// See [`Reactor::create_input`].
+    pub fn create_input(&self, initial: T) -> InputCellId {
+        self.lock().create_input(initial)
+    }
+
+    /// See [`Reactor::create_compute`].
+    pub fn create_compute<F>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        F: Fn(&[T]) -> T + Send + 'static,
+    {
+        self.lock().create_compute(dependencies, compute_func)
+    }
+
+    /// See [`Reactor::create_compute_by_ref`].
+    pub fn create_compute_by_ref<F>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        F: Fn(&[&T]) -> T + Send + 'static,
+    {
+        self.lock()
+            .create_compute_by_ref(dependencies, compute_func)
+    }
+
+    /// See [`Reactor::create_lazy_compute`].
+    pub fn create_lazy_compute<F>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        F: Fn(&[T]) -> T + Send + 'static,
+    {
+        self.lock().create_lazy_compute(dependencies, compute_func)
+    }
+
+    /// See [`Reactor::create_lazy_compute_by_ref`].
+    pub fn create_lazy_compute_by_ref<F>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        F: Fn(&[&T]) -> T + Send + 'static,
+    {
+        self.lock()
+            .create_lazy_compute_by_ref(dependencies, compute_func)
+    }
+
+    /// See [`Reactor::create_try_compute`].
+    pub fn create_try_compute<E, F>(
+        &self,
+        dependencies: &[CellId],
+        compute_func: F,
+    ) -> Result<ComputeCellId, CellId>
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+        F: Fn(&[T]) -> Result<T, E> + Send + 'static,
+    {
+        self.lock().create_try_compute(dependencies, compute_func)
+    }
+
+    /// See [`Reactor::stats`].
+    pub fn stats(&self, id: ComputeCellId) -> Option<ComputeStats> {
+        self.lock().stats(id)
+    }
+
+    /// See [`Reactor::value`].
+    pub fn value(&self, id: CellId) -> Option<T> {
+        self.lock().value(id)
+    }
+
+    /// See [`Reactor::try_value`].
+    pub fn try_value(&self, id: CellId) -> Option<Result<T, CellError>> {
+        self.lock().try_value(id)
+    }
+
+    /// Runs `f` on the reactor while holding the lock, for reads that need
+    /// more than one cell to be consistent, or that borrow values.
+    pub fn read<R>(&self, f: impl FnOnce(&Reactor<'static, T>) -> R) -> R {
+        f(&self.lock())
+    }
+
+    /// See [`Reactor::remove_input`].
+    pub fn remove_input(&self, id: InputCellId) -> Result<(), RemoveCellError> {
+        self.lock().remove_input(id)
+    }
+
+    /// See [`Reactor::remove_compute`].
+    pub fn remove_compute(&self, id: ComputeCellId) -> Result<(), RemoveCellError> {
+        self.lock().remove_compute(id)
+    }
+
+    /// See [`Reactor::remove_with_dependents`].
+    pub fn remove_with_dependents(
+        &self,
+        id: CellId,
+    ) -> Result<Vec<ComputeCellId>, RemoveCellError> {
+        self.lock().remove_with_dependents(id)
+    }
+
+    /// See [`Reactor::set_value`].
+    pub fn set_value(&self, id: InputCellId, new_value: T) -> bool {
+        self.lock().set_value(id, new_value)
+    }
+
+    /// See [`Reactor::batch`]. No other thread can use the reactor until
+    /// `f` returns and the change has been propagated, so a batch can also
+    /// read and then write inputs without losing updates.
+    pub fn batch<R>(&self, f: impl FnOnce(&mut Transaction<'_, 'static, T>) -> R) -> R {
+        self.lock().batch(f)
+    }
+
+    /// See [`Reactor::set_history_limit`].
+    pub fn set_history_limit(&self, limit: usize) {
+        self.lock().set_history_limit(limit)
+    }
+
+    /// See [`Reactor::undo`].
+    pub fn undo(&self) -> bool {
+        self.lock().undo()
+    }
+
+    /// See [`Reactor::redo`].
+    pub fn redo(&self) -> bool {
+        self.lock().redo()
+    }
+
+    /// See [`Reactor::add_callback`].
+    pub fn add_callback<F>(&self, id: ComputeCellId, callback: F) -> Option<CallbackId>
+    where
+        F: FnMut(T) + Send + 'static,
+    {
+        self.lock().add_callback(id, callback)
+    }
+
+    /// See [`Reactor::add_try_callback`].
+    pub fn add_try_callback<F>(&self, id: ComputeCellId, callback: F) -> Option<CallbackId>
+    where
+        F: FnMut(Result<T, CellError>) + Send + 'static,
+    {
+        self.lock().add_try_callback(id, callback)
+    }
+
+    /// See [`Reactor::subscribe`].
+    pub fn subscribe(&self, id: ComputeCellId) -> Option<Subscription<T>> {
+        self.lock().subscribe(id)
+    }
+
+    /// See [`Reactor::remove_callback`].
+    pub fn remove_callback(
+        &self,
+        cell: ComputeCellId,
+        callback: CallbackId,
+    ) -> Result<(), RemoveCallbackError> {
+        self.lock().remove_callback(cell, callback)
+    }
+
+    /// See [`Reactor::to_dot`].
+    pub fn to_dot(&self) -> String
+    where
+        T: fmt::Debug,
+    {
+        self.lock().to_dot()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SendReactor<T>> {
+        self.reactor
+            .lock()
+            .expect("a compute function or callback panicked")
+    }
+}
+
+impl<T> Deref for SendReactor<T> {
+    type Target = Reactor<'static, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SendReactor<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
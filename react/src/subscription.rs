@@ -0,0 +1,51 @@
+use crate::ComputeCellId;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// A change to the value of a compute cell, as delivered to a
+/// [`Subscription`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change<T> {
+    pub cell: ComputeCellId,
+    pub old: T,
+    pub new: T,
+    /// Numbers the `set_value` call or batch that made the change. Changes
+    /// made together share a number, and each later update that changes a
+    /// compute cell gets the next one, starting from 1.
+    pub sequence: u64,
+}
+
+/// The changes to a compute cell, made by [`Reactor::subscribe`](crate::Reactor::subscribe).
+///
+/// A subscription holds no borrow of the reactor and can be sent to another
+/// thread if `T` can. Iterating blocks until the next change, and ends once
+/// the cell or the reactor is dropped and every change sent has been
+/// received.
+pub struct Subscription<T> {
+    receiver: Receiver<Change<T>>,
+}
+
+impl<T> Subscription<T> {
+    pub(crate) fn new(receiver: Receiver<Change<T>>) -> Self {
+        Subscription { receiver }
+    }
+
+    /// Returns the next change if one has already been made, without
+    /// blocking.
+    pub fn try_next(&self) -> Option<Change<T>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits at most `timeout` for the next change.
+    pub fn next_timeout(&self, timeout: Duration) -> Result<Change<T>, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+}
+
+impl<T> Iterator for Subscription<T> {
+    type Item = Change<T>;
+
+    fn next(&mut self) -> Option<Change<T>> {
+        self.receiver.recv().ok()
+    }
+}
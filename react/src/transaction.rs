@@ -0,0 +1,45 @@
+use crate::{InputCellId, Reactor};
+use std::collections::BTreeMap;
+use std::mem;
+
+/// A set of input changes made by [`Reactor::batch`].
+///
+/// Compute cells are not updated until the batch ends, so nothing downstream
+/// sees the inputs half-changed.
+pub struct Transaction<'r, 'a, T> {
+    reactor: &'r mut Reactor<'a, T>,
+    /// The value each changed input had before the batch.
+    changed: BTreeMap<InputCellId, T>,
+}
+
+impl<'r, 'a, T: Clone + PartialEq> Transaction<'r, 'a, T> {
+    pub(crate) fn new(reactor: &'r mut Reactor<'a, T>) -> Self {
+        Transaction {
+            reactor,
+            changed: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the value of the specified input cell when the batch ends.
+    ///
+    /// Returns false if the cell does not exist. Setting the same cell twice
+    /// keeps the last value.
+    pub fn set(&mut self, id: InputCellId, value: T) -> bool {
+        let Some(input) = self.reactor.input_mut(id) else {
+            return false;
+        };
+        let old = mem::replace(&mut input.value, value);
+        self.changed.entry(id).or_insert(old);
+        true
+    }
+
+    /// The value the specified input cell will have when the batch ends, or
+    /// None if the cell does not exist.
+    pub fn get(&self, id: InputCellId) -> Option<T> {
+        self.reactor.input(id).map(|input| input.value.clone())
+    }
+
+    pub(crate) fn into_changed(self) -> BTreeMap<InputCellId, T> {
+        self.changed
+    }
+}
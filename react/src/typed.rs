@@ -0,0 +1,340 @@
+//! Cells of different types in one reactor.
+//!
+//! A `Reactor<AnyValue>` stores every value type-erased, and hands out
+//! [`InputCell<U>`] and [`ComputeCell<U>`] handles that remember the type of
+//! their cell, so that reading, writing and deriving cells is checked at
+//! compile time:
+//!
+//! ```
+//! use react::{AnyValue, Reactor};
+//!
+//! let mut r = Reactor::<AnyValue>::new();
+//! let count = r.create_typed_input(3u32);
+//! let label = r
+//!     .create_typed_compute((count,), |(n,)| format!("{n} items"))
+//!     .unwrap();
+//! r.set_typed_value(count, 4);
+//! assert_eq!(r.typed_value(label).as_deref(), Some("4 items"));
+//! ```
+//!
+//! Handles cannot be used with a value of the wrong type:
+//!
+//! ```compile_fail
+//! let mut r = react::Reactor::<react::AnyValue>::new();
+//! let count = r.create_typed_input(3u32);
+//! r.set_typed_value(count, "three");
+//! ```
+//!
+//! ```compile_fail
+//! let mut r = react::Reactor::<react::AnyValue>::new();
+//! let name = r.create_typed_input(String::from("Ada"));
+//! let _ = r.create_typed_compute((name,), |(n,): (&u32,)| n + 1);
+//! ```
+//!
+//! ```compile_fail
+//! let mut r = react::Reactor::<react::AnyValue>::new();
+//! let count = r.create_typed_input(3u32);
+//! let label: react::ComputeCell<String> = r
+//!     .create_typed_compute((count,), |(n,)| n + 1)
+//!     .unwrap();
+//! ```
+//!
+//! ```compile_fail
+//! let mut r = react::Reactor::<react::AnyValue>::new();
+//! let count = r.create_typed_input(3u32);
+//! let doubled: react::ComputeCell<u32> = count;
+//! ```
+//!
+//! The untyped API still works on a `Reactor<AnyValue>`, with the IDs from
+//! [`InputCell::id`] and [`ComputeCell::id`]. Setting a typed input cell to a
+//! value of another type that way makes its typed reads return `None`, and
+//! makes its typed dependents hold a [`CellError`](crate::CellError) until it
+//! is set back to a value of its type.
+
+use crate::{CallbackId, CellId, ComputeCellId, InputCellId, Reactor, Transaction};
+use std::any::Any;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// A value of any type, so that one reactor can hold cells of different
+/// types.
+pub struct AnyValue(Box<dyn DynValue>);
+
+trait DynValue: Any {
+    fn clone_box(&self) -> Box<dyn DynValue>;
+    fn eq_dyn(&self, other: &dyn DynValue) -> bool;
+    fn fmt_dyn(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<U: Any + Clone + PartialEq + fmt::Debug> DynValue for U {
+    fn clone_box(&self) -> Box<dyn DynValue> {
+        Box::new(self.clone())
+    }
+
+    fn eq_dyn(&self, other: &dyn DynValue) -> bool {
+        other.as_any().downcast_ref::<U>() == Some(self)
+    }
+
+    fn fmt_dyn(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl AnyValue {
+    pub fn new<U: Any + Clone + PartialEq + fmt::Debug>(value: U) -> Self {
+        AnyValue(Box::new(value))
+    }
+
+    /// Borrows the value, if it is a `U`.
+    pub fn downcast_ref<U: Any>(&self) -> Option<&U> {
+        self.0.as_any().downcast_ref()
+    }
+
+    /// Takes the value, if it is a `U`.
+    pub fn downcast<U: Any>(self) -> Result<U, Self> {
+        if self.downcast_ref::<U>().is_none() {
+            return Err(self);
+        }
+        Ok(*self.0.into_any().downcast().expect("checked above"))
+    }
+}
+
+impl Clone for AnyValue {
+    fn clone(&self) -> Self {
+        AnyValue(self.0.clone_box())
+    }
+}
+
+/// Values of different types are never equal.
+impl PartialEq for AnyValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_dyn(&*other.0)
+    }
+}
+
+impl fmt::Debug for AnyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_dyn(f)
+    }
+}
+
+/// A handle to an input cell holding a `U`.
+pub struct InputCell<U> {
+    id: InputCellId,
+    value: PhantomData<fn() -> U>,
+}
+
+/// A handle to a compute cell holding a `U`.
+pub struct ComputeCell<U> {
+    id: ComputeCellId,
+    value: PhantomData<fn() -> U>,
+}
+
+impl<U> InputCell<U> {
+    pub fn id(self) -> InputCellId {
+        self.id
+    }
+}
+
+impl<U> ComputeCell<U> {
+    pub fn id(self) -> ComputeCellId {
+        self.id
+    }
+}
+
+// Derived impls would needlessly require `U: Clone` and so on.
+macro_rules! handle_impls {
+    ($($handle:ident),*) => {
+        $(
+            impl<U> Clone for $handle<U> {
+                fn clone(&self) -> Self {
+                    *self
+                }
+            }
+
+            impl<U> Copy for $handle<U> {}
+
+            impl<U> PartialEq for $handle<U> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.id == other.id
+                }
+            }
+
+            impl<U> Eq for $handle<U> {}
+
+            impl<U> fmt::Debug for $handle<U> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.debug_tuple(stringify!($handle)).field(&self.id).finish()
+                }
+            }
+        )*
+    };
+}
+
+handle_impls!(InputCell, ComputeCell);
+
+/// A handle to a cell of a known type.
+pub trait TypedCell: Copy {
+    type Value: Any + Clone + PartialEq + fmt::Debug;
+
+    fn cell_id(self) -> CellId;
+}
+
+impl<U: Any + Clone + PartialEq + fmt::Debug> TypedCell for InputCell<U> {
+    type Value = U;
+
+    fn cell_id(self) -> CellId {
+        CellId::Input(self.id)
+    }
+}
+
+impl<U: Any + Clone + PartialEq + fmt::Debug> TypedCell for ComputeCell<U> {
+    type Value = U;
+
+    fn cell_id(self) -> CellId {
+        CellId::Compute(self.id)
+    }
+}
+
+/// The dependencies of a typed compute cell: a tuple of handles, whose
+/// compute function receives a tuple of references to their values.
+pub trait Dependencies {
+    type Refs<'v>;
+
+    fn cell_ids(&self) -> Vec<CellId>;
+
+    /// Recover the typed values from the type-erased arguments of a compute
+    /// function, or None if one of them was set to a value of another type.
+    fn downcast<'v>(values: &[&'v AnyValue]) -> Option<Self::Refs<'v>>;
+}
+
+macro_rules! tuple_dependencies {
+    ($(($($cell:ident $index:tt),+))*) => {
+        $(
+            impl<$($cell: TypedCell),+> Dependencies for ($($cell,)+) {
+                type Refs<'v> = ($(&'v $cell::Value,)+);
+
+                fn cell_ids(&self) -> Vec<CellId> {
+                    vec![$(self.$index.cell_id()),+]
+                }
+
+                fn downcast<'v>(values: &[&'v AnyValue]) -> Option<Self::Refs<'v>> {
+                    Some(($(values[$index].downcast_ref::<$cell::Value>()?,)+))
+                }
+            }
+        )*
+    };
+}
+
+tuple_dependencies! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+}
+
+impl<'a> Reactor<'a, AnyValue> {
+    /// Creates an input cell of type `U`.
+    pub fn create_typed_input<U: Any + Clone + PartialEq + fmt::Debug>(
+        &mut self,
+        initial: U,
+    ) -> InputCell<U> {
+        InputCell {
+            id: self.create_input(AnyValue::new(initial)),
+            value: PhantomData,
+        }
+    }
+
+    /// Creates a compute cell of type `U` from a tuple of typed cells.
+    ///
+    /// The compute function borrows the dependencies' values, as a tuple in
+    /// the same order. While a dependency holds a value of another type,
+    /// having been set through the untyped API, the cell holds a
+    /// [`CellError`](crate::CellError) instead.
+    pub fn create_typed_compute<D, U, F>(
+        &mut self,
+        dependencies: D,
+        compute_func: F,
+    ) -> Result<ComputeCell<U>, CellId>
+    where
+        D: Dependencies,
+        U: Any + Clone + PartialEq + fmt::Debug,
+        F: for<'v> Fn(D::Refs<'v>) -> U + 'a,
+    {
+        let function = move |args: &[&AnyValue]| {
+            let refs = D::downcast(args).ok_or("a dependency holds a value of another type")?;
+            Ok(AnyValue::new(compute_func(refs)))
+        };
+        let id = self.insert_compute(&dependencies.cell_ids(), Box::new(function), false)?;
+        Ok(ComputeCell {
+            id,
+            value: PhantomData,
+        })
+    }
+
+    /// Retrieves the current value of a typed cell, or None if the cell does
+    /// not exist.
+    pub fn typed_value<C: TypedCell>(&self, cell: C) -> Option<C::Value> {
+        self.typed_value_ref(cell).cloned()
+    }
+
+    /// Borrows the current value of a typed cell, or returns None if the cell
+    /// does not exist.
+    pub fn typed_value_ref<C: TypedCell>(&self, cell: C) -> Option<&C::Value> {
+        self.value_ref(cell.cell_id())?.downcast_ref()
+    }
+
+    /// Sets the value of a typed input cell.
+    ///
+    /// Returns false if the cell does not exist.
+    pub fn set_typed_value<U: Any + Clone + PartialEq + fmt::Debug>(
+        &mut self,
+        cell: InputCell<U>,
+        value: U,
+    ) -> bool {
+        self.set_value(cell.id, AnyValue::new(value))
+    }
+
+    /// Adds a callback to a typed compute cell, with the same semantics as
+    /// [`Reactor::add_callback`].
+    pub fn add_typed_callback<U, F>(
+        &mut self,
+        cell: ComputeCell<U>,
+        mut callback: F,
+    ) -> Option<CallbackId>
+    where
+        U: Any + Clone + PartialEq + fmt::Debug,
+        F: FnMut(U) + 'a,
+    {
+        self.add_callback(cell.id, move |value: AnyValue| {
+            if let Ok(value) = value.downcast() {
+                callback(value);
+            }
+        })
+    }
+}
+
+impl Transaction<'_, '_, AnyValue> {
+    /// Sets the value of a typed input cell when the batch ends.
+    ///
+    /// Returns false if the cell does not exist.
+    pub fn set_typed<U: Any + Clone + PartialEq + fmt::Debug>(
+        &mut self,
+        cell: InputCell<U>,
+        value: U,
+    ) -> bool {
+        self.set(cell.id, AnyValue::new(value))
+    }
+}
@@ -0,0 +1,112 @@
+use react::*;
+use std::cell::RefCell;
+
+#[test]
+fn batch_fires_each_callback_once_with_the_final_value() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    reactor.add_callback(sum, |v| calls.borrow_mut().push(v));
+
+    reactor.batch(|tx| {
+        assert!(tx.set(a, 10));
+        assert!(tx.set(b, 20));
+    });
+    assert_eq!(reactor.value(CellId::Compute(sum)), Some(30));
+    assert_eq!(*calls.borrow(), [30]);
+}
+
+#[test]
+fn compute_functions_never_see_a_partial_batch() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(1);
+    let _ = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| {
+            seen.borrow_mut().push((v[0], v[1]));
+            v[0] * v[1]
+        })
+        .unwrap();
+
+    reactor.batch(|tx| {
+        tx.set(a, 2);
+        tx.set(b, 3);
+    });
+    assert_eq!(*seen.borrow(), [(1, 1), (2, 3)]);
+}
+
+#[test]
+fn diamonds_are_recomputed_once_in_topological_order() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let left = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let right = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let _ = reactor
+        .create_compute(&[CellId::Compute(left), CellId::Compute(right)], |v| {
+            seen.borrow_mut().push((v[0], v[1]));
+            v[0] + v[1]
+        })
+        .unwrap();
+
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(*seen.borrow(), [(2, 2), (6, 10)]);
+}
+
+#[test]
+fn setting_an_input_twice_keeps_the_last_value() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    reactor.add_callback(double, |v| calls.borrow_mut().push(v));
+
+    reactor.batch(|tx| {
+        tx.set(input, 2);
+        assert_eq!(tx.get(input), Some(2));
+        tx.set(input, 3);
+    });
+    assert_eq!(*calls.borrow(), [6]);
+}
+
+#[test]
+fn a_batch_that_changes_nothing_fires_no_callbacks() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    reactor.add_callback(double, |v| calls.borrow_mut().push(v));
+
+    reactor.batch(|tx| {
+        tx.set(input, 7);
+        tx.set(input, 1);
+    });
+    reactor.batch(|_| ());
+    assert!(calls.borrow().is_empty());
+}
+
+#[test]
+fn batch_rejects_nonexistent_inputs_and_returns_the_closure_result() {
+    let mut dummy_reactor = Reactor::new();
+    let _ = dummy_reactor.create_input(1);
+    let dummy = dummy_reactor.create_input(2);
+
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let accepted = reactor.batch(|tx| (tx.set(input, 5), tx.set(dummy, 5)));
+    assert_eq!(accepted, (true, false));
+    assert_eq!(reactor.value(CellId::Input(input)), Some(5));
+}
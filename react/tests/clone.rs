@@ -0,0 +1,97 @@
+use react::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[test]
+fn strings_can_be_computed_from_strings() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let name = reactor.create_input(String::from("world"));
+    let greeting = reactor
+        .create_compute_by_ref(&[CellId::Input(name)], |v| format!("hello, {}", v[0]))
+        .unwrap();
+    reactor.add_callback(greeting, |v| calls.borrow_mut().push(v));
+
+    assert!(reactor.set_value(name, String::from("reactor")));
+    assert!(reactor.set_value(name, String::from("reactor")));
+    assert_eq!(
+        reactor.value(CellId::Compute(greeting)).as_deref(),
+        Some("hello, reactor")
+    );
+    assert_eq!(*calls.borrow(), ["hello, reactor"]);
+}
+
+#[test]
+fn vectors_can_be_computed_by_value() {
+    let mut reactor = Reactor::new();
+    let items = reactor.create_input(vec![3, 1, 2]);
+    let sorted = reactor
+        .create_compute(&[CellId::Input(items)], |v| {
+            let mut sorted = v[0].clone();
+            sorted.sort();
+            sorted
+        })
+        .unwrap();
+    assert_eq!(
+        reactor.value_ref(CellId::Compute(sorted)),
+        Some(&vec![1, 2, 3])
+    );
+
+    assert!(reactor.set_value(items, vec![9, 8]));
+    assert_eq!(reactor.value(CellId::Compute(sorted)), Some(vec![8, 9]));
+}
+
+#[test]
+fn arcs_are_shared_not_copied() {
+    let mut reactor = Reactor::new();
+    let big = Arc::new(vec![0u8; 1024]);
+    let input = reactor.create_input(Arc::clone(&big));
+    let same = reactor
+        .create_compute_by_ref(&[CellId::Input(input)], |v| Arc::clone(v[0]))
+        .unwrap();
+    let value = reactor.value(CellId::Compute(same)).unwrap();
+    assert!(Arc::ptr_eq(&big, &value));
+}
+
+/// Counts how often it is cloned.
+#[derive(Debug)]
+struct Tracked(Rc<RefCell<usize>>, i32);
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        *self.0.borrow_mut() += 1;
+        Tracked(Rc::clone(&self.0), self.1)
+    }
+}
+
+impl PartialEq for Tracked {
+    fn eq(&self, other: &Self) -> bool {
+        self.1 == other.1
+    }
+}
+
+#[test]
+fn by_ref_compute_functions_do_not_clone_their_arguments() {
+    let clones = Rc::new(RefCell::new(0));
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(Tracked(Rc::clone(&clones), 1));
+    let b = reactor.create_input(Tracked(Rc::clone(&clones), 2));
+    let sum = reactor
+        .create_compute_by_ref(&[CellId::Input(a), CellId::Input(b)], |v| {
+            Tracked(Rc::clone(&v[0].0), v[0].1 + v[1].1)
+        })
+        .unwrap();
+    let lazy = reactor
+        .create_lazy_compute_by_ref(&[CellId::Compute(sum)], |v| {
+            Tracked(Rc::clone(&v[0].0), v[0].1 * 10)
+        })
+        .unwrap();
+
+    assert!(reactor.set_value(a, Tracked(Rc::clone(&clones), 5)));
+    assert_eq!(
+        reactor.value_ref(CellId::Compute(lazy)).map(|t| t.1),
+        Some(70)
+    );
+    assert_eq!(*clones.borrow(), 0);
+}
@@ -0,0 +1,204 @@
+use react::*;
+use std::cell::RefCell;
+
+fn divide(v: &[i32]) -> Result<i32, String> {
+    if v[1] == 0 {
+        Err(format!("cannot divide {} by zero", v[0]))
+    } else {
+        Ok(v[0] / v[1])
+    }
+}
+
+#[test]
+fn try_compute_cells_hold_values_or_errors() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    assert_eq!(reactor.try_value(CellId::Compute(quotient)), Some(Ok(5)));
+
+    assert!(reactor.set_value(b, 0));
+    let error = reactor
+        .try_value(CellId::Compute(quotient))
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.cell(), quotient);
+    assert_eq!(error.error().to_string(), "cannot divide 10 by zero");
+    assert_eq!(reactor.value(CellId::Compute(quotient)), None);
+
+    assert!(reactor.set_value(b, 5));
+    assert_eq!(reactor.value(CellId::Compute(quotient)), Some(2));
+}
+
+#[test]
+fn try_value_of_a_nonexistent_cell_is_none() {
+    let mut dummy = Reactor::<i32>::new();
+    let input = dummy.create_input(1);
+    let output = dummy
+        .create_try_compute(&[CellId::Input(input)], |v| Ok::<_, String>(v[0]))
+        .unwrap();
+
+    let reactor = Reactor::<i32>::new();
+    assert_eq!(reactor.try_value(CellId::Input(input)), None);
+    assert_eq!(reactor.try_value(CellId::Compute(output)), None);
+}
+
+#[test]
+fn creating_a_failing_cell_is_not_an_error() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_try_compute(&[CellId::Input(input)], |_| Err::<i32, _>("never works"))
+        .unwrap();
+    assert!(reactor.try_value(CellId::Compute(output)).unwrap().is_err());
+}
+
+#[test]
+fn errors_propagate_without_running_dependent_compute_functions() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    let plus_one = reactor
+        .create_compute(&[CellId::Compute(quotient)], |v| v[0] + 1)
+        .unwrap();
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Compute(plus_one)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+
+    assert!(reactor.set_value(b, 0));
+    let error = reactor
+        .try_value(CellId::Compute(sum))
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.cell(), quotient);
+    assert_eq!(
+        reactor.try_value(CellId::Compute(plus_one)),
+        Some(Err(error))
+    );
+    assert_eq!(reactor.stats(plus_one).unwrap().evaluations, 1);
+    assert_eq!(reactor.stats(sum).unwrap().evaluations, 1);
+
+    assert!(reactor.set_value(a, 20));
+    assert_eq!(reactor.stats(sum).unwrap().evaluations, 1);
+
+    assert!(reactor.set_value(b, 4));
+    assert_eq!(reactor.value(CellId::Compute(sum)), Some(26));
+}
+
+#[test]
+fn lazy_cells_pick_up_errors_when_read() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    let lazy = reactor
+        .create_lazy_compute(&[CellId::Compute(quotient)], |v| v[0] * 2)
+        .unwrap();
+
+    assert!(reactor.set_value(b, 0));
+    assert_eq!(reactor.value(CellId::Compute(lazy)), None);
+    assert!(reactor.set_value(b, 1));
+    assert_eq!(reactor.value(CellId::Compute(lazy)), Some(20));
+}
+
+#[test]
+fn try_callbacks_hear_about_failing_and_recovering() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    let plus_one = reactor
+        .create_compute(&[CellId::Compute(quotient)], |v| v[0] + 1)
+        .unwrap();
+    reactor
+        .add_try_callback(plus_one, |outcome| {
+            seen.borrow_mut()
+                .push(outcome.map_err(|error| error.error().to_string()))
+        })
+        .unwrap();
+
+    assert!(reactor.set_value(b, 0));
+    // Failing the same way again is not a change.
+    assert!(reactor.set_value(b, 0));
+    assert!(reactor.set_value(a, 20));
+    assert!(reactor.set_value(b, 10));
+    assert_eq!(
+        *seen.borrow(),
+        [
+            Err(String::from("cannot divide 10 by zero")),
+            Err(String::from("cannot divide 20 by zero")),
+            Ok(3),
+        ]
+    );
+}
+
+#[test]
+fn plain_callbacks_only_see_values() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    reactor
+        .add_callback(quotient, |v| seen.borrow_mut().push(v))
+        .unwrap();
+
+    assert!(reactor.set_value(b, 0));
+    assert!(reactor.set_value(b, 2));
+    assert!(reactor.set_value(b, 5));
+    assert_eq!(*seen.borrow(), [5, 2]);
+}
+
+#[test]
+fn subscriptions_skip_errors() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(10);
+    let b = reactor.create_input(2);
+    let quotient = reactor
+        .create_try_compute(&[CellId::Input(a), CellId::Input(b)], divide)
+        .unwrap();
+    let changes = reactor.subscribe(quotient).unwrap();
+
+    assert!(reactor.set_value(b, 0));
+    assert!(reactor.set_value(b, 5));
+    assert!(reactor.set_value(b, 1));
+    // Recovering to the value before the error is no change.
+    assert!(reactor.set_value(b, 0));
+    assert!(reactor.set_value(b, 1));
+    drop(reactor);
+
+    let changes: Vec<_> = changes.map(|c| (c.old, c.new)).collect();
+    assert_eq!(changes, [(5, 2), (2, 10)]);
+}
+
+#[test]
+fn cell_errors_display_their_origin_and_message() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(0);
+    let output = reactor
+        .create_try_compute(&[CellId::Input(input)], |_| Err::<i32, _>("no luck"))
+        .unwrap();
+
+    let error = reactor
+        .try_value(CellId::Compute(output))
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), format!("{output:?} failed: no luck"));
+    assert!(reactor
+        .to_dot()
+        .contains(&format!("error: {output:?} failed: no luck")));
+}
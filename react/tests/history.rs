@@ -0,0 +1,174 @@
+use react::*;
+use std::cell::RefCell;
+
+#[test]
+fn history_is_off_by_default() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 2));
+    assert!(!reactor.can_undo());
+    assert!(!reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(2));
+}
+
+#[test]
+fn undo_and_redo_step_through_changes() {
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let input = reactor.create_input(1);
+    for n in 2..=4 {
+        assert!(reactor.set_value(input, n));
+    }
+
+    assert!(reactor.undo());
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(2));
+    assert!(reactor.can_redo());
+    assert!(reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(3));
+    assert!(reactor.undo());
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(1));
+    assert!(!reactor.undo());
+    assert!(reactor.redo());
+    assert!(reactor.redo());
+    assert!(reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(4));
+    assert!(!reactor.redo());
+}
+
+#[test]
+fn a_batch_is_undone_as_one_step() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    reactor
+        .add_callback(sum, |v| seen.borrow_mut().push(v))
+        .unwrap();
+
+    reactor.batch(|tx| {
+        tx.set(a, 10);
+        tx.set(a, 20);
+        tx.set(b, 30);
+    });
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(1));
+    assert_eq!(reactor.value(CellId::Input(b)), Some(2));
+    assert!(!reactor.can_undo());
+    assert!(reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(20));
+    assert_eq!(*seen.borrow(), [50, 3, 50]);
+}
+
+#[test]
+fn undo_fires_callbacks_like_any_change() {
+    let seen = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let input = reactor.create_input(1);
+    let parity = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] % 2)
+        .unwrap();
+    reactor
+        .add_callback(parity, |v| seen.borrow_mut().push(v))
+        .unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 4));
+    assert!(reactor.undo());
+    assert!(reactor.undo());
+    assert_eq!(*seen.borrow(), [0, 1]);
+}
+
+#[test]
+fn changes_that_leave_inputs_as_they_were_are_not_recorded() {
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 1));
+    reactor.batch(|tx| {
+        tx.set(input, 5);
+        tx.set(input, 1);
+    });
+    assert!(!reactor.can_undo());
+}
+
+#[test]
+fn a_new_change_forgets_what_could_be_redone() {
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let input = reactor.create_input(1);
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.undo());
+    assert!(reactor.set_value(input, 3));
+    assert!(!reactor.redo());
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(1));
+}
+
+#[test]
+fn history_depth_is_bounded() {
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(3);
+    let input = reactor.create_input(0);
+    for n in 1..=10 {
+        assert!(reactor.set_value(input, n));
+    }
+
+    let mut undone = 0;
+    while reactor.undo() {
+        undone += 1;
+    }
+    assert_eq!(undone, 3);
+    assert_eq!(reactor.value(CellId::Input(input)), Some(7));
+
+    reactor.set_history_limit(1);
+    assert!(reactor.redo());
+    assert!(!reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(input)), Some(8));
+}
+
+#[test]
+fn undo_skips_removed_inputs() {
+    let mut reactor = Reactor::new();
+    reactor.set_history_limit(10);
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    reactor.batch(|tx| {
+        tx.set(a, 10);
+        tx.set(b, 20);
+    });
+    assert!(reactor.remove_input(b).is_ok());
+
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(1));
+    assert!(reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(10));
+
+    // A change to inputs that are all gone is dropped, and the one before it
+    // is undone instead.
+    let c = reactor.create_input(3);
+    reactor.set_value(c, 30);
+    assert!(reactor.remove_input(c).is_ok());
+    assert!(reactor.undo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(1));
+    assert!(!reactor.undo());
+    assert!(!reactor.can_undo());
+
+    // The same goes for redoing.
+    let d = reactor.create_input(4);
+    reactor.set_value(d, 40);
+    reactor.set_value(a, 20);
+    assert!(reactor.undo());
+    assert!(reactor.undo());
+    assert!(reactor.remove_input(d).is_ok());
+    assert!(reactor.redo());
+    assert_eq!(reactor.value(CellId::Input(a)), Some(20));
+    assert!(!reactor.redo());
+    assert!(!reactor.can_redo());
+}
@@ -0,0 +1,152 @@
+use react::*;
+
+#[test]
+fn dependencies_and_dependents_follow_the_graph() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let sum = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    let twice = reactor
+        .create_compute(&[CellId::Compute(sum), CellId::Compute(sum)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+
+    assert_eq!(
+        reactor.dependencies(sum),
+        Some(&[CellId::Input(a), CellId::Input(b)][..])
+    );
+    assert_eq!(
+        reactor.dependencies(twice),
+        Some(&[CellId::Compute(sum), CellId::Compute(sum)][..])
+    );
+    assert_eq!(reactor.dependents(CellId::Input(a)), Some(&[sum][..]));
+    assert_eq!(reactor.dependents(CellId::Compute(sum)), Some(&[twice][..]));
+    assert_eq!(reactor.dependents(CellId::Compute(twice)), Some(&[][..]));
+}
+
+#[test]
+fn removed_cells_cannot_be_inspected() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0])
+        .unwrap();
+    assert!(reactor.remove_compute(output).is_ok());
+
+    assert_eq!(reactor.inspect(CellId::Compute(output)), None);
+    assert_eq!(reactor.dependencies(output), None);
+    assert_eq!(reactor.dependents(CellId::Compute(output)), None);
+    assert_eq!(reactor.dependents(CellId::Input(input)), Some(&[][..]));
+    assert_eq!(reactor.cells().count(), 1);
+}
+
+#[test]
+fn inspect_reports_values_callbacks_and_stats() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(2);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 10)
+        .unwrap();
+    reactor.add_callback(output, |_| ()).unwrap();
+    reactor.add_callback(output, |_| ()).unwrap();
+    let _subscription = reactor.subscribe(output).unwrap();
+
+    let info = reactor.inspect(CellId::Input(input)).unwrap();
+    assert_eq!(info.value, Some(&2));
+    assert_eq!(info.dependents, &[output][..]);
+    assert_eq!(info.stats, None);
+
+    let info = reactor.inspect(CellId::Compute(output)).unwrap();
+    assert_eq!(info.value, Some(&20));
+    assert_eq!(info.dependencies, &[CellId::Input(input)][..]);
+    assert_eq!(info.callbacks, 2);
+    assert_eq!(info.subscribers, 1);
+    assert!(!info.lazy);
+    assert_eq!(info.stats.unwrap().evaluations, 1);
+}
+
+#[test]
+fn inspect_does_not_compute_stale_lazy_cells() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(2);
+    let lazy = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+
+    let info = reactor.inspect(CellId::Compute(lazy)).unwrap();
+    assert_eq!(info.value, None);
+    assert!(info.lazy);
+    assert_eq!(reactor.stats(lazy).unwrap().evaluations, 0);
+
+    assert_eq!(reactor.value(CellId::Compute(lazy)), Some(3));
+    let info = reactor.inspect(CellId::Compute(lazy)).unwrap();
+    assert_eq!(info.value, Some(&3));
+}
+
+#[test]
+fn cells_lists_inputs_then_computes_in_creation_order() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let first = reactor
+        .create_compute(&[CellId::Input(a)], |v| v[0])
+        .unwrap();
+    let b = reactor.create_input(2);
+    let second = reactor
+        .create_compute(&[CellId::Input(b)], |v| v[0])
+        .unwrap();
+
+    let ids: Vec<_> = reactor.cells().map(|info| info.id).collect();
+    assert_eq!(
+        ids,
+        [
+            CellId::Input(a),
+            CellId::Input(b),
+            CellId::Compute(first),
+            CellId::Compute(second),
+        ]
+    );
+}
+
+#[test]
+fn dot_export_shows_lazy_cells_and_escapes_labels() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(String::from("say \"hi\""));
+    let lazy = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0].clone())
+        .unwrap();
+    let eager = reactor
+        .create_compute(&[CellId::Compute(lazy)], |v| v[0].len().to_string())
+        .unwrap();
+    reactor.add_callback(eager, |_| ()).unwrap();
+
+    let expected = "digraph reactor {
+    input0 [shape=box, label=\"input 0\\n\\\"say \\\\\\\"hi\\\\\\\"\\\"\"];
+    compute0 [style=dashed, label=\"compute 0 (lazy)\\n\\\"say \\\\\\\"hi\\\\\\\"\\\"\\nevaluated 1, 0 callbacks\"];
+    compute1 [label=\"compute 1\\n\\\"8\\\"\\nevaluated 1, 1 callbacks\"];
+    input0 -> compute0;
+    compute0 -> compute1;
+}
+";
+    assert_eq!(reactor.to_dot(), expected);
+
+    assert!(reactor.set_value(input, String::from("x")));
+    assert!(reactor
+        .to_dot()
+        .contains("compute1 [label=\"compute 1\\n\\\"1\\\"\\nevaluated 2, 1 callbacks\"];"));
+}
+
+#[test]
+fn dot_export_marks_stale_lazy_cells() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let _ = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+
+    assert!(reactor.to_dot().contains(
+        "compute0 [style=dashed, label=\"compute 0 (lazy)\\n(stale)\\nevaluated 0, 0 callbacks\"];"
+    ));
+}
@@ -0,0 +1,120 @@
+use react::*;
+use std::cell::RefCell;
+
+#[test]
+fn lazy_cells_compute_on_first_read() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(3);
+    let square = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] * v[0])
+        .unwrap();
+
+    let untouched = ComputeStats {
+        evaluations: 0,
+        invalidations: 0,
+    };
+    assert_eq!(reactor.stats(square), Some(untouched));
+    assert_eq!(reactor.value(CellId::Compute(square)), Some(9));
+    assert_eq!(reactor.value(CellId::Compute(square)), Some(9));
+    assert_eq!(reactor.stats(square).unwrap().evaluations, 1);
+}
+
+#[test]
+fn lazy_cells_recompute_only_when_read_after_a_change() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let square = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] * v[0])
+        .unwrap();
+    let eager = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * v[0])
+        .unwrap();
+
+    for n in 2..=5 {
+        assert!(reactor.set_value(input, n));
+    }
+    let expected = ComputeStats {
+        evaluations: 0,
+        invalidations: 4,
+    };
+    assert_eq!(reactor.stats(square), Some(expected));
+    assert_eq!(reactor.stats(eager).unwrap().evaluations, 5);
+
+    assert_eq!(reactor.value(CellId::Compute(square)), Some(25));
+    assert_eq!(reactor.stats(square).unwrap().evaluations, 1);
+}
+
+#[test]
+fn reading_a_cell_pulls_stale_lazy_dependencies() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let doubled = reactor
+        .create_lazy_compute(&[CellId::Compute(plus_one)], |v| v[0] * 2)
+        .unwrap();
+
+    assert!(reactor.set_value(input, 10));
+    assert_eq!(reactor.value(CellId::Compute(doubled)), Some(22));
+    assert_eq!(reactor.stats(plus_one).unwrap().evaluations, 1);
+    assert_eq!(reactor.stats(doubled).unwrap().evaluations, 1);
+}
+
+#[test]
+fn eager_cells_see_up_to_date_lazy_dependencies() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let doubled = reactor
+        .create_compute(&[CellId::Compute(plus_one)], |v| v[0] * 2)
+        .unwrap();
+    reactor.add_callback(doubled, |v| calls.borrow_mut().push(v));
+
+    assert!(reactor.set_value(input, 2));
+    assert!(reactor.set_value(input, 3));
+    assert_eq!(*calls.borrow(), [6, 8]);
+}
+
+#[test]
+fn attaching_a_callback_keeps_a_lazy_cell_current() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let parity = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] % 2)
+        .unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    let callback = reactor
+        .add_callback(parity, |v| calls.borrow_mut().push(v))
+        .unwrap();
+    assert_eq!(reactor.stats(parity).unwrap().evaluations, 1);
+
+    assert!(reactor.set_value(input, 4));
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(*calls.borrow(), [1]);
+    assert_eq!(reactor.stats(parity).unwrap().evaluations, 3);
+
+    assert_eq!(reactor.remove_callback(parity, callback), Ok(()));
+    assert!(reactor.set_value(input, 6));
+    assert_eq!(reactor.stats(parity).unwrap().evaluations, 3);
+    assert_eq!(reactor.value(CellId::Compute(parity)), Some(0));
+}
+
+#[test]
+fn stats_of_nonexistent_cells() {
+    let mut dummy_reactor = Reactor::new();
+    let input = dummy_reactor.create_input(1);
+    let output = dummy_reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0])
+        .unwrap();
+    assert_eq!(Reactor::<i32>::new().stats(output), None);
+    assert_eq!(
+        Reactor::new().create_lazy_compute(&[CellId::Input(input)], |v: &[i32]| v[0]),
+        Err(CellId::Input(input))
+    );
+}
@@ -0,0 +1,141 @@
+use react::*;
+use std::cell::RefCell;
+
+#[test]
+fn removed_cells_no_longer_exist() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+
+    assert_eq!(reactor.remove_compute(output), Ok(()));
+    assert_eq!(reactor.value(CellId::Compute(output)), None);
+    assert_eq!(reactor.add_callback(output, |_| ()), None);
+    assert_eq!(
+        reactor.remove_compute(output),
+        Err(RemoveCellError::NonexistentCell)
+    );
+
+    assert_eq!(reactor.remove_input(input), Ok(()));
+    assert_eq!(reactor.value(CellId::Input(input)), None);
+    assert!(!reactor.set_value(input, 2));
+    assert_eq!(
+        reactor.create_compute(&[CellId::Input(input)], |v| v[0]),
+        Err(CellId::Input(input))
+    );
+}
+
+#[test]
+fn cells_with_dependents_are_not_removed() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let triple = reactor
+        .create_compute(&[CellId::Input(input), CellId::Input(input)], |v| {
+            v[0] + v[1] + v[0]
+        })
+        .unwrap();
+    let sum = reactor
+        .create_compute(&[CellId::Compute(double), CellId::Compute(triple)], |v| {
+            v[0] + v[1]
+        })
+        .unwrap();
+
+    assert_eq!(
+        reactor.remove_input(input),
+        Err(RemoveCellError::HasDependents(vec![double, triple]))
+    );
+    assert_eq!(
+        reactor.remove_compute(double),
+        Err(RemoveCellError::HasDependents(vec![sum]))
+    );
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(reactor.value(CellId::Compute(sum)), Some(10));
+}
+
+#[test]
+fn a_cell_can_be_removed_once_its_dependents_are_gone() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let double = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let quadruple = reactor
+        .create_compute(&[CellId::Compute(double)], |v| v[0] * 2)
+        .unwrap();
+
+    assert_eq!(reactor.remove_compute(quadruple), Ok(()));
+    assert_eq!(reactor.remove_compute(double), Ok(()));
+    assert_eq!(reactor.remove_input(input), Ok(()));
+}
+
+#[test]
+fn removing_a_compute_cell_stops_its_callbacks_but_not_its_siblings() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let minus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] - 1)
+        .unwrap();
+    reactor.add_callback(plus_one, |v| calls.borrow_mut().push(("plus", v)));
+    reactor.add_callback(minus_one, |v| calls.borrow_mut().push(("minus", v)));
+
+    assert_eq!(reactor.remove_compute(plus_one), Ok(()));
+    assert!(reactor.set_value(input, 10));
+    assert_eq!(*calls.borrow(), [("minus", 9)]);
+}
+
+#[test]
+fn removal_with_dependents_cascades() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(2);
+    let a_plus_b = reactor
+        .create_compute(&[CellId::Input(a), CellId::Input(b)], |v| v[0] + v[1])
+        .unwrap();
+    let b_squared = reactor
+        .create_compute(&[CellId::Input(b)], |v| v[0] * v[0])
+        .unwrap();
+    let total = reactor
+        .create_compute(&[CellId::Compute(a_plus_b)], |v| v[0] * 10)
+        .unwrap();
+
+    assert_eq!(
+        reactor.remove_with_dependents(CellId::Input(a)),
+        Ok(vec![a_plus_b, total])
+    );
+    assert_eq!(reactor.value(CellId::Compute(total)), None);
+    assert_eq!(reactor.value(CellId::Compute(a_plus_b)), None);
+    assert!(reactor.set_value(b, 3));
+    assert_eq!(reactor.value(CellId::Compute(b_squared)), Some(9));
+
+    assert_eq!(
+        reactor.remove_input(b),
+        Err(RemoveCellError::HasDependents(vec![b_squared]))
+    );
+    assert_eq!(
+        reactor.remove_with_dependents(CellId::Compute(b_squared)),
+        Ok(vec![])
+    );
+    assert_eq!(reactor.remove_input(b), Ok(()));
+    assert_eq!(
+        reactor.remove_with_dependents(CellId::Input(b)),
+        Err(RemoveCellError::NonexistentCell)
+    );
+}
+
+#[test]
+fn ids_of_removed_cells_are_not_reused() {
+    let mut reactor = Reactor::new();
+    let first = reactor.create_input(1);
+    assert_eq!(reactor.remove_input(first), Ok(()));
+    let second = reactor.create_input(2);
+    assert_ne!(first, second);
+    assert_eq!(reactor.value(CellId::Input(first)), None);
+}
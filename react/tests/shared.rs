@@ -0,0 +1,142 @@
+use react::*;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const THREADS: usize = 8;
+const WRITES: usize = 200;
+
+#[test]
+fn shared_reactor_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedReactor<i32>>();
+    assert_send_sync::<SharedReactor<String>>();
+}
+
+#[test]
+fn concurrent_writes_fire_every_callback_exactly_once() {
+    let reactor = SharedReactor::new();
+    let input = reactor.create_input(0);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0])
+        .unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    reactor
+        .add_callback(output, move |v| sink.lock().unwrap().push(v))
+        .unwrap();
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let reactor = &reactor;
+            s.spawn(move || {
+                // Every value written is distinct, so every write is a change.
+                for i in 1..=WRITES {
+                    assert!(reactor.set_value(input, t * WRITES + i));
+                }
+            });
+        }
+    });
+
+    let mut seen = seen.lock().unwrap().clone();
+    assert_eq!(seen.len(), THREADS * WRITES);
+    seen.sort_unstable();
+    assert_eq!(seen, (1..=THREADS * WRITES).collect::<Vec<_>>());
+    let last = reactor.value(CellId::Input(input));
+    assert_eq!(reactor.value(CellId::Compute(output)), last);
+}
+
+#[test]
+fn batches_from_several_threads_do_not_lose_updates() {
+    let reactor = Arc::new(SharedReactor::new());
+    let counter = reactor.create_input(0);
+    let total = reactor
+        .create_compute(&[CellId::Input(counter)], |v| v[0])
+        .unwrap();
+    let calls = Arc::new(Mutex::new(0));
+    let sink = Arc::clone(&calls);
+    reactor
+        .add_callback(total, move |_| *sink.lock().unwrap() += 1)
+        .unwrap();
+
+    let writers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let reactor = Arc::clone(&reactor);
+            thread::spawn(move || {
+                for _ in 0..WRITES {
+                    reactor.batch(|tx| tx.set(counter, tx.get(counter).unwrap() + 1));
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    assert_eq!(
+        reactor.value(CellId::Compute(total)),
+        Some(THREADS * WRITES)
+    );
+    assert_eq!(*calls.lock().unwrap(), THREADS * WRITES);
+}
+
+#[test]
+fn readers_never_see_a_half_propagated_change() {
+    let reactor = SharedReactor::new();
+    let input = reactor.create_input(0);
+    let plus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let minus_one = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] - 1)
+        .unwrap();
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            for i in 1..=1000 {
+                reactor.set_value(input, i);
+            }
+        });
+        s.spawn(|| {
+            for _ in 0..1000 {
+                let (a, b) = reactor.read(|r| {
+                    (
+                        r.value(CellId::Compute(plus_one)).unwrap(),
+                        r.value(CellId::Compute(minus_one)).unwrap(),
+                    )
+                });
+                assert_eq!(a - b, 2);
+            }
+        });
+    });
+}
+
+#[test]
+fn subscriptions_receive_changes_from_every_thread_in_order() {
+    let reactor = SharedReactor::new();
+    let input = reactor.create_input(0);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0])
+        .unwrap();
+    let changes = reactor.subscribe(output).unwrap();
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let reactor = &reactor;
+            s.spawn(move || {
+                for i in 1..=WRITES {
+                    reactor.set_value(input, t * WRITES + i);
+                }
+            });
+        }
+    });
+    drop(reactor);
+
+    let changes: Vec<_> = changes.collect();
+    assert_eq!(changes.len(), THREADS * WRITES);
+    for (expected, change) in (1..).zip(&changes) {
+        assert_eq!(change.sequence, expected);
+    }
+    for pair in changes.windows(2) {
+        assert_eq!(pair[0].new, pair[1].old);
+    }
+}
@@ -0,0 +1,145 @@
+use react::*;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn subscriptions_receive_old_and_new_values() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] + 1)
+        .unwrap();
+    let subscription = reactor.subscribe(output).unwrap();
+
+    assert!(reactor.set_value(input, 5));
+    assert_eq!(
+        subscription.try_next(),
+        Some(Change {
+            cell: output,
+            old: 2,
+            new: 6,
+            sequence: 1,
+        })
+    );
+    assert_eq!(subscription.try_next(), None);
+}
+
+#[test]
+fn subscriptions_only_receive_actual_changes() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] / 10)
+        .unwrap();
+    let subscription = reactor.subscribe(output).unwrap();
+
+    assert!(reactor.set_value(input, 5));
+    assert!(reactor.set_value(input, 25));
+    assert!(reactor.set_value(input, 29));
+    drop(reactor);
+    let changes: Vec<_> = subscription.map(|c| (c.old, c.new)).collect();
+    assert_eq!(changes, [(0, 2)]);
+}
+
+#[test]
+fn changes_made_together_share_a_sequence_number() {
+    let mut reactor = Reactor::new();
+    let a = reactor.create_input(1);
+    let b = reactor.create_input(1);
+    let double_a = reactor
+        .create_compute(&[CellId::Input(a)], |v| v[0] * 2)
+        .unwrap();
+    let double_b = reactor
+        .create_compute(&[CellId::Input(b)], |v| v[0] * 2)
+        .unwrap();
+    let sub_a = reactor.subscribe(double_a).unwrap();
+    let sub_b = reactor.subscribe(double_b).unwrap();
+
+    reactor.batch(|tx| {
+        tx.set(a, 2);
+        tx.set(b, 2);
+    });
+    assert!(reactor.set_value(a, 2));
+    assert!(reactor.set_value(b, 3));
+    drop(reactor);
+
+    let sequences = |sub: Subscription<i32>| sub.map(|c| c.sequence).collect::<Vec<_>>();
+    assert_eq!(sequences(sub_a), [1]);
+    assert_eq!(sequences(sub_b), [1, 2]);
+}
+
+#[test]
+fn several_subscribers_each_receive_every_change() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let first = reactor.subscribe(output).unwrap();
+    let second = reactor.subscribe(output).unwrap();
+    drop(reactor.subscribe(output).unwrap());
+
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(first.try_next().map(|c| c.new), Some(4));
+    assert_eq!(second.try_next().map(|c| c.new), Some(4));
+}
+
+#[test]
+fn subscriptions_end_when_the_cell_is_removed() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let mut subscription = reactor.subscribe(output).unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(reactor.remove_compute(output), Ok(()));
+    assert_eq!(subscription.next().map(|c| c.new), Some(4));
+    assert_eq!(subscription.next(), None);
+    assert_eq!(reactor.subscribe(output).map(|_| ()), None);
+}
+
+#[test]
+fn subscriptions_keep_lazy_cells_current() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(1);
+    let output = reactor
+        .create_lazy_compute(&[CellId::Input(input)], |v| v[0] * 2)
+        .unwrap();
+    let subscription = reactor.subscribe(output).unwrap();
+
+    assert!(reactor.set_value(input, 2));
+    assert_eq!(
+        subscription.try_next().map(|c| (c.old, c.new)),
+        Some((2, 4))
+    );
+}
+
+#[test]
+fn changes_can_be_followed_from_another_thread() {
+    let mut reactor = Reactor::new();
+    let input = reactor.create_input(0);
+    let output = reactor
+        .create_compute(&[CellId::Input(input)], |v| v[0] * 10)
+        .unwrap();
+    let subscription = reactor.subscribe(output).unwrap();
+
+    let follower = thread::spawn(move || {
+        let first = subscription.next_timeout(Duration::from_secs(10)).unwrap();
+        let rest: Vec<_> = subscription.collect();
+        (first, rest)
+    });
+    for n in 1..=100 {
+        assert!(reactor.set_value(input, n));
+    }
+    drop(reactor);
+
+    let (first, rest) = follower.join().unwrap();
+    assert_eq!((first.old, first.new, first.sequence), (0, 10, 1));
+    assert_eq!(rest.len(), 99);
+    for (n, change) in (2..).zip(&rest) {
+        assert_eq!(change.new, n * 10);
+        assert_eq!(change.sequence, n as u64);
+    }
+}
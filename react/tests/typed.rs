@@ -0,0 +1,126 @@
+use react::*;
+use std::cell::RefCell;
+
+#[test]
+fn a_number_cell_can_feed_a_string_cell() {
+    let calls = RefCell::new(Vec::new());
+    let mut reactor = Reactor::<AnyValue>::new();
+    let count = reactor.create_typed_input(1u32);
+    let label = reactor
+        .create_typed_compute((count,), |(n,)| match n {
+            1 => String::from("1 item"),
+            n => format!("{n} items"),
+        })
+        .unwrap();
+    reactor.add_typed_callback(label, |s: String| calls.borrow_mut().push(s));
+
+    assert_eq!(reactor.typed_value(label).as_deref(), Some("1 item"));
+    assert!(reactor.set_typed_value(count, 3));
+    assert_eq!(
+        reactor.typed_value_ref(label).map(String::as_str),
+        Some("3 items")
+    );
+    assert_eq!(reactor.typed_value(count), Some(3));
+    assert_eq!(*calls.borrow(), ["3 items"]);
+}
+
+#[test]
+fn compute_cells_can_mix_input_and_compute_dependencies_of_several_types() {
+    let mut reactor = Reactor::<AnyValue>::new();
+    let price = reactor.create_typed_input(2.5f64);
+    let quantity = reactor.create_typed_input(4u32);
+    let item = reactor.create_typed_input(String::from("apple"));
+    let total = reactor
+        .create_typed_compute((price, quantity), |(p, q)| p * f64::from(*q))
+        .unwrap();
+    let line = reactor
+        .create_typed_compute((quantity, item, total), |(q, i, t)| {
+            format!("{q} x {i} = {t:.2}")
+        })
+        .unwrap();
+    let is_large = reactor
+        .create_typed_compute((total,), |(t,)| *t > 100.0)
+        .unwrap();
+
+    assert_eq!(
+        reactor.typed_value(line).as_deref(),
+        Some("4 x apple = 10.00")
+    );
+    reactor.batch(|tx| {
+        assert!(tx.set_typed(quantity, 50));
+        assert!(tx.set_typed(item, String::from("pear")));
+    });
+    assert_eq!(
+        reactor.typed_value(line).as_deref(),
+        Some("50 x pear = 125.00")
+    );
+    assert_eq!(reactor.typed_value(is_large), Some(true));
+}
+
+#[test]
+fn typed_handles_work_with_the_untyped_api() {
+    let mut reactor = Reactor::<AnyValue>::new();
+    let count = reactor.create_typed_input(1u8);
+    let doubled = reactor
+        .create_typed_compute((count,), |(n,)| n * 2)
+        .unwrap();
+
+    assert_eq!(
+        reactor.value(CellId::Compute(doubled.id())),
+        Some(AnyValue::new(2u8))
+    );
+    let subscription = reactor.subscribe(doubled.id()).unwrap();
+    assert!(reactor.set_typed_value(count, 2));
+    assert_eq!(
+        subscription.try_next().map(|c| c.new),
+        Some(AnyValue::new(4u8))
+    );
+
+    assert_eq!(reactor.remove_compute(doubled.id()), Ok(()));
+    assert_eq!(reactor.typed_value(doubled), None);
+    assert_eq!(
+        reactor.create_typed_compute((doubled,), |(n,)| *n),
+        Err(CellId::Compute(doubled.id()))
+    );
+}
+
+#[test]
+fn values_of_different_types_are_never_equal() {
+    assert_eq!(AnyValue::new(1u32), AnyValue::new(1u32));
+    assert_ne!(AnyValue::new(1u32), AnyValue::new(2u32));
+    assert_ne!(AnyValue::new(1u32), AnyValue::new(1u64));
+    assert_eq!(format!("{:?}", AnyValue::new("x")), "\"x\"");
+    assert_eq!(AnyValue::new(7i8).downcast::<i8>().ok(), Some(7));
+    assert!(AnyValue::new(7i8).downcast::<u8>().is_err());
+}
+
+#[test]
+fn reading_a_typed_cell_set_to_another_type_returns_none() {
+    let mut reactor = Reactor::<AnyValue>::new();
+    let count = reactor.create_typed_input(1u32);
+    assert!(reactor.set_value(count.id(), AnyValue::new("one")));
+    assert_eq!(reactor.typed_value(count), None);
+}
+
+#[test]
+fn dependents_of_a_typed_cell_set_to_another_type_hold_an_error() {
+    let mut reactor = Reactor::<AnyValue>::new();
+    let count = reactor.create_typed_input(1u32);
+    let doubled = reactor
+        .create_typed_compute((count,), |(n,)| n * 2)
+        .unwrap();
+    let label = reactor
+        .create_typed_compute((doubled,), |(n,)| n.to_string())
+        .unwrap();
+
+    assert!(reactor.set_value(count.id(), AnyValue::new("oops")));
+    assert_eq!(reactor.typed_value(doubled), None);
+    let error = reactor
+        .try_value(CellId::Compute(label.id()))
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.cell(), doubled.id());
+
+    assert!(reactor.set_typed_value(count, 3));
+    assert_eq!(reactor.typed_value(label).as_deref(), Some("6"));
+}